@@ -0,0 +1,561 @@
+//! A fixed-capacity priority queue that never allocates.
+//!
+//! [`ArrayHeap`] stores up to `N` elements inline in an array, and uses the
+//! same sifting routines as [`BinaryHeap`](crate::BinaryHeap). It only depends
+//! on `core`, so it can be used in `no_std` and interrupt contexts where there
+//! is no allocator.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::ArrayHeap;
+//!
+//! // The heap can be created in a constant context.
+//! const EMPTY: ArrayHeap<u32, 4> = ArrayHeap::new();
+//!
+//! let mut heap = EMPTY;
+//! for x in [3, 1, 4, 1] {
+//!     heap.try_push(x).unwrap();
+//! }
+//! assert_eq!(heap.try_push(5), Err(5));
+//!
+//! assert_eq!(heap.pop(), Some(4));
+//! assert_eq!(heap.peek(), Some(&3));
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::slice;
+
+use crate::binary_heap::slice::{
+    make_heap_with, pop_heap_with, push_heap_with, sift_down_with, sort_heap_with,
+};
+use crate::compare::{Compare, MaxComparator, MinComparator};
+
+/// A priority queue implemented with a binary heap, stored inline in an array
+/// of `N` elements.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults
+/// to the natural order of `T`.
+///
+/// It is a logic error for an item to be modified in such a way that the
+/// item's ordering relative to any other item changes while it is in the heap.
+/// The behavior resulting from such a logic error is not specified, but will
+/// not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [try\_push]   | [pop]         | [peek]/[peek\_mut] |
+/// |---------------|---------------|--------------------|
+/// | *O*(log(*n*)) | *O*(log(*n*)) | *O*(1)             |
+///
+/// [try\_push]: ArrayHeap::try_push
+/// [pop]: ArrayHeap::pop
+/// [peek]: ArrayHeap::peek
+/// [peek\_mut]: ArrayHeap::peek_mut
+pub struct ArrayHeap<T, const N: usize, C = MaxComparator> {
+    /// The first `len` elements are initialized and form a heap.
+    data: [MaybeUninit<T>; N],
+    len: usize,
+    cmp: C,
+}
+
+/// Structure wrapping a mutable reference to the greatest item on an
+/// `ArrayHeap`.
+///
+/// This `struct` is created by the [`peek_mut`] method on [`ArrayHeap`]. See
+/// its documentation for more.
+///
+/// [`peek_mut`]: ArrayHeap::peek_mut
+pub struct PeekMut<'a, T: 'a, const N: usize, C: 'a + Compare<T> = MaxComparator> {
+    heap: &'a mut ArrayHeap<T, N, C>,
+    sift: bool,
+}
+
+impl<T: fmt::Debug, const N: usize, C: Compare<T>> fmt::Debug for PeekMut<'_, T, N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeekMut")
+            .field(&self.heap.as_slice()[0])
+            .finish()
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> Drop for PeekMut<'_, T, N, C> {
+    fn drop(&mut self) {
+        if self.sift {
+            let (data, cmp) = self.heap.parts_mut();
+            sift_down_with::<2, _, _>(data, cmp, 0);
+        }
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> Deref for PeekMut<'_, T, N, C> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.heap.as_slice()[0]
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> DerefMut for PeekMut<'_, T, N, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.sift = true;
+        &mut self.heap.parts_mut().0[0]
+    }
+}
+
+impl<'a, T, const N: usize, C: Compare<T>> PeekMut<'a, T, N, C> {
+    /// Removes the peeked value from the heap and returns it.
+    pub fn pop(mut this: PeekMut<'a, T, N, C>) -> T {
+        let value = this.heap.pop().unwrap();
+        this.sift = false;
+        value
+    }
+}
+
+impl<T, const N: usize, C> Drop for ArrayHeap<T, N, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize, C: Clone> Clone for ArrayHeap<T, N, C> {
+    fn clone(&self) -> Self {
+        let mut clone = ArrayHeap::with_cmp(self.cmp.clone());
+        for item in self.as_slice() {
+            // Increment the length as we go, so that already cloned elements
+            // are dropped if a later clone panics.
+            clone.data[clone.len].write(item.clone());
+            clone.len += 1;
+        }
+        clone
+    }
+}
+
+impl<T, const N: usize, C: Compare<T> + Default> Default for ArrayHeap<T, N, C> {
+    /// Creates an empty `ArrayHeap<T, N, C>`.
+    #[inline]
+    fn default() -> Self {
+        ArrayHeap::with_cmp(C::default())
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, C> fmt::Debug for ArrayHeap<T, N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord, const N: usize> ArrayHeap<T, N> {
+    /// Creates an empty `ArrayHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 8> = ArrayHeap::new();
+    /// heap.try_push(4).unwrap();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        ArrayHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T: Ord, const N: usize> ArrayHeap<T, N, MinComparator> {
+    /// Creates an empty `ArrayHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 8, _> = ArrayHeap::new_min();
+    /// heap.try_push(3).unwrap();
+    /// heap.try_push(1).unwrap();
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub const fn new_min() -> Self {
+        ArrayHeap::with_cmp(MinComparator)
+    }
+}
+
+impl<T, const N: usize, C> ArrayHeap<T, N, C> {
+    /// Creates an empty `ArrayHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{ArrayHeap, KeyComparator};
+    /// let mut heap: ArrayHeap<i32, 8, _> = ArrayHeap::with_cmp(KeyComparator(|x: &i32| x.abs()));
+    /// heap.try_push(3).unwrap();
+    /// heap.try_push(-7).unwrap();
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub const fn with_cmp(cmp: C) -> Self {
+        ArrayHeap {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+            cmp,
+        }
+    }
+
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 8> = ArrayHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.try_push(1).unwrap();
+    /// heap.try_push(5).unwrap();
+    /// heap.try_push(2).unwrap();
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// Returns an iterator visiting all values in the heap, in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Returns a slice of all values in the heap, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 8> = ArrayHeap::new();
+    /// heap.try_push(1).unwrap();
+    /// heap.try_push(3).unwrap();
+    ///
+    /// assert_eq!(heap.as_slice().len(), 2);
+    /// ```
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.data.as_ptr().cast::<T>(), self.len) }
+    }
+
+    /// Returns the number of elements in the heap.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the heap is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks if the heap holds `N` elements, so that pushing fails.
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the number of elements the heap can hold, which is always `N`.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Drops all items from the heap.
+    pub fn clear(&mut self) {
+        let len = mem::take(&mut self.len);
+        // SAFETY: the first `len` elements are initialized, and setting the
+        //  length to 0 first means they can't be dropped twice.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.data.as_mut_ptr().cast::<T>(),
+                len,
+            ))
+        };
+    }
+
+    /// Returns the initialized elements along with the comparator, so that
+    /// both can be borrowed at once.
+    fn parts_mut(&mut self) -> (&mut [T], &C) {
+        // SAFETY: the first `len` elements are initialized.
+        let data =
+            unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr().cast::<T>(), self.len) };
+        (data, &self.cmp)
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> ArrayHeap<T, N, C> {
+    /// Returns a mutable reference to the greatest item in the heap, or `None`
+    /// if it is empty.
+    ///
+    /// Note: If the `PeekMut` value is leaked, the heap may be in an
+    /// inconsistent state.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 8> = ArrayHeap::new();
+    /// assert!(heap.peek_mut().is_none());
+    ///
+    /// heap.try_push(1).unwrap();
+    /// heap.try_push(5).unwrap();
+    /// heap.try_push(2).unwrap();
+    /// {
+    ///     let mut val = heap.peek_mut().unwrap();
+    ///     *val = 0;
+    /// }
+    /// assert_eq!(heap.peek(), Some(&2));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// If the item is modified then the worst case time complexity is *O*(log(*n*)),
+    /// otherwise it's *O*(1).
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, N, C>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut {
+                heap: self,
+                sift: false,
+            })
+        }
+    }
+
+    /// Pushes an item onto the heap, or returns it as `Err(item)` if the heap
+    /// is full.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap: ArrayHeap<i32, 2> = ArrayHeap::new();
+    ///
+    /// assert_eq!(heap.try_push(3), Ok(()));
+    /// assert_eq!(heap.try_push(5), Ok(()));
+    /// assert_eq!(heap.try_push(1), Err(1));
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `try_push` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    pub fn try_push(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.data[self.len].write(item);
+        self.len += 1;
+        let (data, cmp) = self.parts_mut();
+        push_heap_with::<2, _, _>(data, cmp);
+        Ok(())
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    /// let mut heap = ArrayHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let (data, cmp) = self.parts_mut();
+        pop_heap_with::<2, _, _>(data, cmp);
+        self.len -= 1;
+        // SAFETY: the element at the old `len - 1` was initialized, and is no
+        //  longer part of the heap after decrementing the length.
+        Some(unsafe { self.data[self.len].assume_init_read() })
+    }
+
+    /// Returns an iterator which retrieves elements in heap order.
+    /// The retrieved elements are removed from the original heap.
+    /// The remaining elements will be removed on drop in heap order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    ///
+    /// let mut heap = ArrayHeap::from([1, 2, 3, 4, 5]);
+    /// assert_eq!(heap.len(), 5);
+    ///
+    /// let mut drain = heap.drain_sorted();
+    /// assert_eq!(drain.next(), Some(5));
+    /// assert_eq!(drain.next(), Some(4));
+    /// drop(drain); // removes the remaining elements in heap order
+    /// assert_eq!(heap.len(), 0);
+    /// ```
+    #[inline]
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, N, C> {
+        DrainSorted { inner: self }
+    }
+
+    /// Consumes a full `ArrayHeap` and returns its elements in sorted
+    /// (ascending) order, without moving them out of the array.
+    ///
+    /// Returns `Err(self)` if the heap holds fewer than `N` elements. Use
+    /// [`drain_sorted`](ArrayHeap::drain_sorted) to retrieve the elements of a
+    /// heap that isn't full.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::ArrayHeap;
+    ///
+    /// let mut heap: ArrayHeap<i32, 4> = ArrayHeap::new();
+    /// for x in [3, 1, 4] {
+    ///     heap.try_push(x).unwrap();
+    /// }
+    /// let mut heap = heap.into_sorted_array().unwrap_err();
+    ///
+    /// heap.try_push(1).unwrap();
+    /// assert_eq!(heap.into_sorted_array().ok(), Some([1, 1, 3, 4]));
+    /// ```
+    pub fn into_sorted_array(mut self) -> Result<[T; N], Self> {
+        if !self.is_full() {
+            return Err(self);
+        }
+        let (data, cmp) = self.parts_mut();
+        sort_heap_with::<2, _, _>(data, cmp);
+
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the heap is full, so all `N` elements are initialized, and
+        //  `[MaybeUninit<T>; N]` has the same layout as `[T; N]`. `this` is
+        //  never dropped, so the elements and the comparator are moved out
+        //  exactly once.
+        unsafe {
+            drop(ptr::read(&this.cmp));
+            Ok(ptr::read(this.data.as_ptr().cast::<[T; N]>()))
+        }
+    }
+}
+
+/// A draining iterator over the elements of an `ArrayHeap`, in heap order.
+///
+/// This `struct` is created by [`ArrayHeap::drain_sorted()`]. See its
+/// documentation for more.
+pub struct DrainSorted<'a, T, const N: usize, C: Compare<T> = MaxComparator> {
+    inner: &'a mut ArrayHeap<T, N, C>,
+}
+
+impl<T: fmt::Debug, const N: usize, C: Compare<T>> fmt::Debug for DrainSorted<'_, T, N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DrainSorted").field(&self.inner).finish()
+    }
+}
+
+impl<'a, T, const N: usize, C: Compare<T>> Drop for DrainSorted<'a, T, N, C> {
+    /// Removes heap elements in heap order.
+    fn drop(&mut self) {
+        struct DropGuard<'r, 'a, T, const N: usize, C: Compare<T>>(
+            &'r mut DrainSorted<'a, T, N, C>,
+        );
+
+        impl<'r, 'a, T, const N: usize, C: Compare<T>> Drop for DropGuard<'r, 'a, T, N, C> {
+            fn drop(&mut self) {
+                while self.0.inner.pop().is_some() {}
+            }
+        }
+
+        while let Some(item) = self.inner.pop() {
+            let guard = DropGuard(self);
+            drop(item);
+            mem::forget(guard);
+        }
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> Iterator for DrainSorted<'_, T, N, C> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let exact = self.inner.len();
+        (exact, Some(exact))
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> ExactSizeIterator for DrainSorted<'_, T, N, C> {}
+
+impl<T, const N: usize, C: Compare<T>> FusedIterator for DrainSorted<'_, T, N, C> {}
+
+impl<T: Ord, const N: usize> From<[T; N]> for ArrayHeap<T, N> {
+    /// Converts a `[T; N]` into a full `ArrayHeap<T, N>`.
+    ///
+    /// This conversion happens in-place, and has *O*(*n*) time complexity.
+    fn from(arr: [T; N]) -> Self {
+        let arr = ManuallyDrop::new(arr);
+        let mut heap = ArrayHeap {
+            // SAFETY: `[T; N]` has the same layout as `[MaybeUninit<T>; N]`,
+            //  and `arr` is never dropped.
+            data: unsafe { ptr::read((&*arr as *const [T; N]).cast::<[MaybeUninit<T>; N]>()) },
+            len: N,
+            cmp: MaxComparator,
+        };
+        let (data, cmp) = heap.parts_mut();
+        make_heap_with::<2, _, _>(data, cmp);
+        heap
+    }
+}
+
+impl<'a, T, const N: usize, C> IntoIterator for &'a ArrayHeap<T, N, C> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -1,4 +1,4 @@
-//! A priority queue implemented with a binary heap.
+//! A priority queue implemented with a binary heap, or more generally a *d*-ary heap.
 //!
 //! Insertion and popping the largest element have *O*(log(*n*)) time complexity.
 //! Checking the largest element is *O*(1). Converting a vector to a binary heap
@@ -6,6 +6,10 @@
 //! converted to a sorted vector in-place, allowing it to be used for an *O*(*n* * log(*n*))
 //! in-place heapsort.
 //!
+//! The underlying algorithms also work in place on plain slices; see the
+//! [`slice`](mod@slice) module. A heap keeps its elements in a `Vec` by default,
+//! but can use other buffers too; see the [`storage`] module.
+//!
 //! # Examples
 //!
 //! This is a larger example that implements [Dijkstra's algorithm][dijkstra]
@@ -18,7 +22,7 @@
 //!
 //! ```
 //! use std::cmp::Ordering;
-//! use std::collections::BinaryHeap;
+//! use proptest_binary_heap_example::BinaryHeap;
 //!
 //! #[derive(Copy, Clone, Eq, PartialEq)]
 //! struct State {
@@ -142,32 +146,59 @@
 
 #![allow(missing_docs)]
 
+use core::cmp::Ordering;
+use core::error::Error;
 use core::fmt;
 use core::iter::{FromIterator, FusedIterator};
+use core::marker::PhantomData;
 use core::mem::{self, swap, ManuallyDrop};
 use core::ops::{Deref, DerefMut};
 use core::ptr;
 
-use std::collections::TryReserveError;
-use std::slice;
-use std::vec::{self, Vec};
+use alloc::collections::TryReserveError;
+use alloc::vec::{self, Vec};
 
-/// A priority queue implemented with a binary heap.
+use crate::compare::{Compare, FnComparator, KeyComparator, MaxComparator, MinComparator};
+use crate::queue::PriorityQueue;
+
+use self::slice::{make_heap_with, pop_heap_with, push_heap_with, sort_heap_with};
+use self::storage::HeapStorage;
+
+#[cfg(feature = "allocator-api2")]
+mod allocator;
+pub mod slice;
+pub mod storage;
+
+/// A priority queue implemented with a *d*-ary heap, where every element has
+/// at most `D` children.
+///
+/// [`BinaryHeap`] is the usual case of `D = 2`. Higher arities make the heap
+/// shallower, so `push` does fewer comparisons and `pop` touches fewer cache
+/// lines on large heaps, at the cost of comparing more children at every
+/// level on the way down. `D` must be at least 2.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults
+/// to the natural order of `T`.
 ///
-/// This will be a max-heap.
+/// The elements are kept in the storage `S`, which defaults to a `Vec<T>`. Any
+/// [`HeapStorage`] implementation can be used instead, such as a fixed array or
+/// a borrowed vector; see the [`storage`] module. Methods that expose the
+/// vector itself, like [`into_vec`](DaryHeap::into_vec) or
+/// [`capacity`](DaryHeap::capacity), are only available with the default
+/// storage.
 ///
 /// It is a logic error for an item to be modified in such a way that the
 /// item's ordering relative to any other item, as determined by the [`Ord`]
-/// trait, changes while it is in the heap. This is normally only possible
-/// through [`Cell`], [`RefCell`], global state, I/O, or unsafe code. The
-/// behavior resulting from such a logic error is not specified (it
-/// could include panics, incorrect results, aborts, memory leaks, or
-/// non-termination) but will not be undefined behavior.
+/// trait or the heap's [`Compare`] implementation, changes while it is in the
+/// heap. This is normally only possible through [`Cell`], [`RefCell`], global
+/// state, I/O, or unsafe code. The behavior resulting from such a logic error
+/// is not specified (it could include panics, incorrect results, aborts,
+/// memory leaks, or non-termination) but will not be undefined behavior.
 ///
 /// # Examples
 ///
 /// ```
-/// use std::collections::BinaryHeap;
+/// use proptest_binary_heap_example::BinaryHeap;
 ///
 /// // Type inference lets us omit an explicit type signature (which
 /// // would be `BinaryHeap<i32>` in this example).
@@ -207,22 +238,50 @@ use std::vec::{self, Vec};
 /// assert!(heap.is_empty())
 /// ```
 ///
+/// A heap of any arity can be created through [`DaryHeap`]:
+///
+/// ```
+/// use proptest_binary_heap_example::DaryHeap;
+///
+/// let mut heap = DaryHeap::<_, 4>::new();
+/// heap.extend([1, 5, 2, 4, 3]);
+/// assert_eq!(heap.into_sorted_vec(), [1, 2, 3, 4, 5]);
+/// ```
+///
 /// A `BinaryHeap` with a known list of items can be initialized from an array:
 ///
 /// ```
-/// use std::collections::BinaryHeap;
+/// use proptest_binary_heap_example::BinaryHeap;
 ///
 /// let heap = BinaryHeap::from([1, 5, 2]);
 /// ```
 ///
 /// ## Min-heap
 ///
-/// Either [`core::cmp::Reverse`] or a custom [`Ord`] implementation can be used to
-/// make `BinaryHeap` a min-heap. This makes `heap.pop()` return the smallest
-/// value instead of the greatest one.
+/// [`BinaryHeap::new_min`], [`core::cmp::Reverse`] or a custom [`Ord`] implementation
+/// can be used to make `BinaryHeap` a min-heap. This makes `heap.pop()` return the
+/// smallest value instead of the greatest one.
+///
+/// ```
+/// use proptest_binary_heap_example::BinaryHeap;
+///
+/// let mut heap = BinaryHeap::new_min();
+///
+/// heap.push(1);
+/// heap.push(5);
+/// heap.push(2);
+///
+/// // If we pop these scores now, they should come back in the reverse order.
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(2));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), None);
+/// ```
+///
+/// Wrapping values in `Reverse` has the same effect:
 ///
 /// ```
-/// use std::collections::BinaryHeap;
+/// use proptest_binary_heap_example::BinaryHeap;
 /// use std::cmp::Reverse;
 ///
 /// let mut heap = BinaryHeap::new();
@@ -239,11 +298,33 @@ use std::vec::{self, Vec};
 /// assert_eq!(heap.pop(), None);
 /// ```
 ///
+/// ## Custom comparators
+///
+/// More generally, the order of a heap is determined by its [`Compare`]
+/// implementation. [`BinaryHeap::new_by_key`] orders elements by a key, and
+/// [`BinaryHeap::new_by`] takes an arbitrary comparison closure.
+///
+/// ```
+/// use proptest_binary_heap_example::BinaryHeap;
+///
+/// let mut heap = BinaryHeap::new_by(|a: &(u32, &str), b: &(u32, &str)| {
+///     // Order by the string, then by the number in reverse.
+///     a.1.cmp(b.1).then(b.0.cmp(&a.0))
+/// });
+/// heap.push((1, "b"));
+/// heap.push((2, "b"));
+/// heap.push((3, "a"));
+///
+/// assert_eq!(heap.pop(), Some((1, "b")));
+/// assert_eq!(heap.pop(), Some((2, "b")));
+/// assert_eq!(heap.pop(), Some((3, "a")));
+/// ```
+///
 /// # Time complexity
 ///
-/// | [push]  | [pop]         | [peek]/[peek\_mut] |
-/// |---------|---------------|--------------------|
-/// | *O*(1)~ | *O*(log(*n*)) | *O*(1)             |
+/// | [push]  | [pop]             | [peek]/[peek\_mut] |
+/// |---------|-------------------|--------------------|
+/// | *O*(1)~ | *O*(*D* log(*n*)) | *O*(1)             |
 ///
 /// The value for `push` is an expected cost; the method documentation gives a
 /// more detailed analysis.
@@ -256,10 +337,18 @@ use std::vec::{self, Vec};
 /// [pop]: BinaryHeap::pop
 /// [peek]: BinaryHeap::peek
 /// [peek\_mut]: BinaryHeap::peek_mut
-pub struct BinaryHeap<T> {
-    data: Vec<T>,
+pub struct DaryHeap<T, const D: usize, C = MaxComparator, S = Vec<T>> {
+    data: S,
+    cmp: C,
+    marker: PhantomData<T>,
 }
 
+/// A priority queue implemented with a binary heap.
+///
+/// This is a [`DaryHeap`] where every element has at most two children. See
+/// the documentation of [`DaryHeap`] for more.
+pub type BinaryHeap<T, C = MaxComparator, S = Vec<T>> = DaryHeap<T, 2, C, S>;
+
 /// Structure wrapping a mutable reference to the greatest item on a
 /// `BinaryHeap`.
 ///
@@ -267,18 +356,26 @@ pub struct BinaryHeap<T> {
 /// its documentation for more.
 ///
 /// [`peek_mut`]: BinaryHeap::peek_mut
-pub struct PeekMut<'a, T: 'a + Ord> {
-    heap: &'a mut BinaryHeap<T>,
+pub struct PeekMut<
+    'a,
+    T: 'a,
+    const D: usize = 2,
+    C: 'a + Compare<T> = MaxComparator,
+    S: 'a + HeapStorage<T> = Vec<T>,
+> {
+    heap: &'a mut DaryHeap<T, D, C, S>,
     sift: bool,
 }
 
-impl<T: Ord + fmt::Debug> fmt::Debug for PeekMut<'_, T> {
+impl<T: fmt::Debug, const D: usize, C: Compare<T>, S: HeapStorage<T>> fmt::Debug
+    for PeekMut<'_, T, D, C, S>
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("PeekMut").field(&self.heap.data[0]).finish()
+        f.debug_tuple("PeekMut").field(&**self).finish()
     }
 }
 
-impl<T: Ord> Drop for PeekMut<'_, T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Drop for PeekMut<'_, T, D, C, S> {
     fn drop(&mut self) {
         if self.sift {
             // SAFETY: PeekMut is only instantiated for non-empty heaps.
@@ -287,60 +384,93 @@ impl<T: Ord> Drop for PeekMut<'_, T> {
     }
 }
 
-impl<T: Ord> Deref for PeekMut<'_, T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Deref for PeekMut<'_, T, D, C, S> {
     type Target = T;
     fn deref(&self) -> &T {
         debug_assert!(!self.heap.is_empty());
         // SAFE: PeekMut is only instantiated for non-empty heaps
-        unsafe { self.heap.data.get_unchecked(0) }
+        unsafe { self.heap.data.as_slice().get_unchecked(0) }
     }
 }
 
-impl<T: Ord> DerefMut for PeekMut<'_, T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> DerefMut for PeekMut<'_, T, D, C, S> {
     fn deref_mut(&mut self) -> &mut T {
         debug_assert!(!self.heap.is_empty());
         self.sift = true;
         // SAFE: PeekMut is only instantiated for non-empty heaps
-        unsafe { self.heap.data.get_unchecked_mut(0) }
+        unsafe { self.heap.data.as_mut_slice().get_unchecked_mut(0) }
     }
 }
 
-impl<'a, T: Ord> PeekMut<'a, T> {
+impl<'a, T, const D: usize, C: Compare<T>, S: HeapStorage<T>> PeekMut<'a, T, D, C, S> {
     /// Removes the peeked value from the heap and returns it.
-    pub fn pop(mut this: PeekMut<'a, T>) -> T {
+    pub fn pop(mut this: PeekMut<'a, T, D, C, S>) -> T {
         let value = this.heap.pop().unwrap();
         this.sift = false;
         value
     }
 }
 
-impl<T: Clone> Clone for BinaryHeap<T> {
+/// An error returned by [`DaryHeap::check_invariants`], pointing at an element
+/// that compares greater than its parent.
+///
+/// The indices are positions in the heap's underlying storage, as seen by
+/// [`as_slice`](DaryHeap::as_slice).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HeapViolation {
+    /// The index of the parent.
+    pub parent: usize,
+    /// The index of the child that compares greater than its parent.
+    pub child: usize,
+}
+
+impl fmt::Display for HeapViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "heap property violated: the element at index {} is greater than \
+             its parent at index {}",
+            self.child, self.parent
+        )
+    }
+}
+
+impl Error for HeapViolation {}
+
+impl<T, const D: usize, C: Clone, S: Clone> Clone for DaryHeap<T, D, C, S> {
     fn clone(&self) -> Self {
-        BinaryHeap {
+        DaryHeap {
             data: self.data.clone(),
+            cmp: self.cmp.clone(),
+            marker: PhantomData,
         }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.data.clone_from(&source.data);
+        self.cmp.clone_from(&source.cmp);
     }
 }
 
-impl<T: Ord> Default for BinaryHeap<T> {
-    /// Creates an empty `BinaryHeap<T>`.
+impl<T, const D: usize, C, S> Default for DaryHeap<T, D, C, S>
+where
+    C: Compare<T> + Default,
+    S: HeapStorage<T> + Default,
+{
+    /// Creates an empty `BinaryHeap<T, C, S>`.
     #[inline]
-    fn default() -> BinaryHeap<T> {
-        BinaryHeap::new()
+    fn default() -> DaryHeap<T, D, C, S> {
+        DaryHeap::from_storage(S::default(), C::default())
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for BinaryHeap<T> {
+impl<T: fmt::Debug, const D: usize, C, S: HeapStorage<T>> fmt::Debug for DaryHeap<T, D, C, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
 }
 
-impl<T: Ord> BinaryHeap<T> {
+impl<T: Ord, const D: usize> DaryHeap<T, D> {
     /// Creates an empty `BinaryHeap` as a max-heap.
     ///
     /// # Examples
@@ -348,13 +478,13 @@ impl<T: Ord> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::new();
     /// heap.push(4);
     /// ```
     #[must_use]
-    pub fn new() -> BinaryHeap<T> {
-        BinaryHeap { data: vec![] }
+    pub fn new() -> DaryHeap<T, D> {
+        DaryHeap::from_vec_cmp(Vec::new(), MaxComparator)
     }
 
     /// Creates an empty `BinaryHeap` with a specific capacity.
@@ -367,15 +497,246 @@ impl<T: Ord> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::with_capacity(10);
     /// heap.push(4);
     /// ```
     #[must_use]
-    pub fn with_capacity(capacity: usize) -> BinaryHeap<T> {
-        BinaryHeap {
-            data: Vec::with_capacity(capacity),
+    pub fn with_capacity(capacity: usize) -> DaryHeap<T, D> {
+        DaryHeap::from_vec_cmp(Vec::with_capacity(capacity), MaxComparator)
+    }
+}
+
+impl<T: Ord, const D: usize> DaryHeap<T, D, MinComparator> {
+    /// Creates an empty `BinaryHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new_min();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// heap.push(5);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        DaryHeap::from_vec_cmp(Vec::new(), MinComparator)
+    }
+
+    /// Creates an empty `BinaryHeap` as a min-heap with a specific capacity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::with_capacity_min(10);
+    /// assert!(heap.capacity() >= 10);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn with_capacity_min(capacity: usize) -> Self {
+        DaryHeap::from_vec_cmp(Vec::with_capacity(capacity), MinComparator)
+    }
+}
+
+impl<T, const D: usize, F> DaryHeap<T, D, FnComparator<F>>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Creates an empty `BinaryHeap` ordered by the comparison closure `f`.
+    ///
+    /// The element that compares greatest according to `f` is at the top of the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new_by(|a: &i32, b: &i32| b.cmp(a));
+    /// heap.push(3);
+    /// heap.push(1);
+    /// heap.push(5);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_by(f: F) -> Self {
+        DaryHeap::from_vec_cmp(Vec::new(), FnComparator(f))
+    }
+
+    /// Creates an empty `BinaryHeap` ordered by the comparison closure `f`, with a specific
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::with_capacity_by(10, |a: &i32, b: &i32| b.cmp(a));
+    /// assert!(heap.capacity() >= 10);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn with_capacity_by(capacity: usize, f: F) -> Self {
+        DaryHeap::from_vec_cmp(Vec::with_capacity(capacity), FnComparator(f))
+    }
+}
+
+impl<T, const D: usize, F, K: Ord> DaryHeap<T, D, KeyComparator<F>>
+where
+    F: Fn(&T) -> K,
+{
+    /// Creates an empty `BinaryHeap` ordered by the key that `f` extracts from each element.
+    ///
+    /// The element with the greatest key is at the top of the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new_by_key(|a: &i32| a.abs());
+    /// heap.push(3);
+    /// heap.push(-7);
+    /// heap.push(5);
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub fn new_by_key(f: F) -> Self {
+        DaryHeap::from_vec_cmp(Vec::new(), KeyComparator(f))
+    }
+
+    /// Creates an empty `BinaryHeap` ordered by the key that `f` extracts from each element, with
+    /// a specific capacity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::with_capacity_by_key(10, |a: &i32| a.abs());
+    /// assert!(heap.capacity() >= 10);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn with_capacity_by_key(capacity: usize, f: F) -> Self {
+        DaryHeap::from_vec_cmp(Vec::with_capacity(capacity), KeyComparator(f))
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>> DaryHeap<T, D, C> {
+    /// Creates a `BinaryHeap` out of the elements of `vec`, ordered by the comparator `cmp`.
+    ///
+    /// This conversion happens in-place, and has *O*(*n*) time complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{BinaryHeap, MinComparator};
+    /// let heap = BinaryHeap::from_vec_cmp(vec![3, 1, 5], MinComparator);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    #[must_use]
+    pub fn from_vec_cmp(vec: Vec<T>, cmp: C) -> Self {
+        DaryHeap::from_storage(vec, cmp)
+    }
+
+    /// Consumes the `BinaryHeap` and returns a vector in sorted
+    /// (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from([1, 2, 4, 5, 7]);
+    /// heap.push(6);
+    /// heap.push(3);
+    ///
+    /// let vec = heap.into_sorted_vec();
+    /// assert_eq!(vec, [1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        sort_heap_with::<D, _, _>(&mut self.data, &self.cmp);
+        self.into_vec()
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut a = BinaryHeap::from([-10, 1, 2, 3, 3]);
+    /// let mut b = BinaryHeap::from([-20, 5, 43]);
+    ///
+    /// a.append(&mut b);
+    ///
+    /// assert_eq!(a.into_sorted_vec(), [-20, -10, 1, 2, 3, 3, 5, 43]);
+    /// assert!(b.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        if self.len() < other.len() {
+            swap(&mut self.data, &mut other.data);
         }
+
+        let start = self.data.len();
+
+        self.data.append(&mut other.data);
+
+        self.rebuild_tail(start);
+        self.debug_check_invariants();
+    }
+}
+
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> DaryHeap<T, D, C, S> {
+    /// Creates a heap out of the elements of `storage`, ordered by the
+    /// comparator `cmp`.
+    ///
+    /// This conversion happens in-place, and has *O*(*n*) time complexity.
+    /// See the [`storage`] module for the kinds of storage that are available.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::binary_heap::storage::ArrayStorage;
+    /// use proptest_binary_heap_example::{BinaryHeap, MaxComparator};
+    ///
+    /// let mut heap = BinaryHeap::from_storage(ArrayStorage::<_, 4>::new(), MaxComparator);
+    /// heap.push(3);
+    /// heap.push(5);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    #[must_use]
+    pub fn from_storage(storage: S, cmp: C) -> Self {
+        const { assert!(D >= 2, "a d-ary heap must have an arity of at least 2") };
+        let mut heap = DaryHeap {
+            data: storage,
+            cmp,
+            marker: PhantomData,
+        };
+        heap.rebuild();
+        heap.debug_check_invariants();
+        heap
     }
 
     /// Returns a mutable reference to the greatest item in the binary heap, or
@@ -389,7 +750,7 @@ impl<T: Ord> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::new();
     /// assert!(heap.peek_mut().is_none());
     ///
@@ -407,7 +768,7 @@ impl<T: Ord> BinaryHeap<T> {
     ///
     /// If the item is modified then the worst case time complexity is *O*(log(*n*)),
     /// otherwise it's *O*(1).
-    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, D, C, S>> {
         if self.is_empty() {
             None
         } else {
@@ -426,7 +787,7 @@ impl<T: Ord> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::from([1, 3]);
     ///
     /// assert_eq!(heap.pop(), Some(3));
@@ -438,14 +799,17 @@ impl<T: Ord> BinaryHeap<T> {
     ///
     /// The worst case cost of `pop` on a heap containing *n* elements is *O*(log(*n*)).
     pub fn pop(&mut self) -> Option<T> {
-        self.data.pop().map(|mut item| {
-            if !self.is_empty() {
-                swap(&mut item, &mut self.data[0]);
-                // SAFETY: !self.is_empty() means that self.len() > 0
-                unsafe { self.sift_down_to_bottom(0) };
-            }
-            item
-        })
+        let item = self.pop_unchecked();
+        self.debug_check_invariants();
+        item
+    }
+
+    /// Like [`pop`](DaryHeap::pop), but without the `debug-invariants` check,
+    /// for destructors that may run while unwinding from a panic that left the
+    /// heap out of order.
+    fn pop_unchecked(&mut self) -> Option<T> {
+        pop_heap_with::<D, _, _>(self.data.as_mut_slice(), &self.cmp);
+        self.data.pop()
     }
 
     /// Pushes an item onto the binary heap.
@@ -455,7 +819,7 @@ impl<T: Ord> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::new();
     /// heap.push(3);
     /// heap.push(5);
@@ -481,84 +845,115 @@ impl<T: Ord> BinaryHeap<T> {
     /// occurs when capacity is exhausted and needs a resize. The resize cost
     /// has been amortized in the previous figures.
     pub fn push(&mut self, item: T) {
-        let old_len = self.len();
         self.data.push(item);
-        // SAFETY: Since we pushed a new item it means that
-        //  old_len = self.len() - 1 < self.len()
-        unsafe { self.sift_up(0, old_len) };
+        push_heap_with::<D, _, _>(self.data.as_mut_slice(), &self.cmp);
+        self.debug_check_invariants();
     }
 
-    /// Consumes the `BinaryHeap` and returns a vector in sorted
-    /// (ascending) order.
+    /// Pushes an item onto the binary heap, then removes the greatest item
+    /// and returns it.
+    ///
+    /// If `item` is at least as large as the greatest item in the heap, it is
+    /// returned immediately and the heap is not modified. Otherwise the
+    /// greatest item is swapped out for `item`, which is then sifted down.
+    /// This is equivalent to, but more efficient than, [`push`] followed by
+    /// [`pop`], and is known as `heappushpop` in Python.
+    ///
+    /// [`push`]: DaryHeap::push
+    /// [`pop`]: DaryHeap::pop
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
-    ///
-    /// let mut heap = BinaryHeap::from([1, 2, 4, 5, 7]);
-    /// heap.push(6);
-    /// heap.push(3);
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::from([1, 5, 2]);
     ///
-    /// let vec = heap.into_sorted_vec();
-    /// assert_eq!(vec, [1, 2, 3, 4, 5, 6, 7]);
+    /// assert_eq!(heap.push_pop(7), 7);
+    /// assert_eq!(heap.push_pop(3), 5);
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
     /// ```
-    #[must_use = "`self` will be dropped if the result is not used"]
-    pub fn into_sorted_vec(mut self) -> Vec<T> {
-        let mut end = self.len();
-        while end > 1 {
-            end -= 1;
-            // SAFETY: `end` goes from `self.len() - 1` to 1 (both included),
-            //  so it's always a valid index to access.
-            //  It is safe to access index 0 (i.e. `ptr`), because
-            //  1 <= end < self.len(), which means self.len() >= 2.
-            unsafe {
-                let ptr = self.data.as_mut_ptr();
-                ptr::swap(ptr, ptr.add(end));
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push_pop` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    #[must_use = "if you don't need the returned item, use `push` instead"]
+    pub fn push_pop(&mut self, mut item: T) -> T {
+        if let Some(top) = self.data.as_mut_slice().first_mut() {
+            if self.cmp.compare(&item, top) == Ordering::Less {
+                swap(&mut item, top);
+                // SAFETY: the heap is not empty, so 0 < self.len()
+                unsafe { self.sift_down(0) };
+                self.debug_check_invariants();
             }
-            // SAFETY: `end` goes from `self.len() - 1` to 1 (both included) so:
-            //  0 < 1 <= end <= self.len() - 1 < self.len()
-            //  Which means 0 < end and end < self.len().
-            unsafe { self.sift_down_range(0, end) };
         }
-        self.into_vec()
+        item
     }
 
-    // The implementations of sift_up and sift_down use unsafe blocks in
-    // order to move an element out of the vector (leaving behind a
-    // hole), shift along the others and move the removed element back into the
-    // vector at the final location of the hole.
-    // The `Hole` type is used to represent this, and make sure
-    // the hole is filled back at the end of its scope, even on panic.
-    // Using a hole reduces the constant factor compared to using swaps,
-    // which involves twice as many moves.
+    /// Removes the greatest item from the binary heap and returns it, then
+    /// pushes `item` onto the heap.
+    ///
+    /// If the heap is empty, `item` is pushed and `None` is returned.
+    /// Otherwise the greatest item is swapped out for `item`, which is then
+    /// sifted down. This is equivalent to, but more efficient than, [`pop`]
+    /// followed by [`push`], and is known as `heapreplace` in Python. Unlike
+    /// [`push_pop`], the returned item may be smaller than `item`.
+    ///
+    /// [`push`]: DaryHeap::push
+    /// [`pop`]: DaryHeap::pop
+    /// [`push_pop`]: DaryHeap::push_pop
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new();
+    ///
+    /// assert_eq!(heap.replace(3), None);
+    /// assert_eq!(heap.replace(7), Some(3));
+    /// assert_eq!(heap.replace(1), Some(7));
+    /// assert_eq!(heap.into_sorted_vec(), [1]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `replace` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    pub fn replace(&mut self, mut item: T) -> Option<T> {
+        let popped = match self.data.as_mut_slice().first_mut() {
+            Some(top) => {
+                swap(&mut item, top);
+                // SAFETY: the heap is not empty, so 0 < self.len()
+                unsafe { self.sift_down(0) };
+                Some(item)
+            }
+            None => {
+                self.data.push(item);
+                None
+            }
+        };
+        self.debug_check_invariants();
+        popped
+    }
 
     /// # Safety
     ///
     /// The caller must guarantee that `pos < self.len()`.
     unsafe fn sift_up(&mut self, start: usize, pos: usize) -> usize {
-        // Take out the value at `pos` and create a hole.
         // SAFETY: The caller guarantees that pos < self.len()
-        let mut hole = unsafe { Hole::new(&mut self.data, pos) };
-
-        while hole.pos() > start {
-            let parent = (hole.pos() - 1) / 2;
-
-            // SAFETY: hole.pos() > start >= 0, which means hole.pos() > 0
-            //  and so hole.pos() - 1 can't underflow.
-            //  This guarantees that parent < hole.pos() so
-            //  it's a valid index and also != hole.pos().
-            if hole.element() <= unsafe { hole.get(parent) } {
-                break;
-            }
-
-            // SAFETY: Same as above
-            unsafe { hole.move_to(parent) };
+        unsafe {
+            sift_up::<D, _, _, _>(
+                self.data.as_mut_slice(),
+                &self.cmp,
+                &mut NoTrack,
+                start,
+                pos,
+            )
         }
-
-        hole.pos()
     }
 
     /// Take an element at `pos` and move it down the heap,
@@ -569,39 +964,15 @@ impl<T: Ord> BinaryHeap<T> {
     /// The caller must guarantee that `pos < end <= self.len()`.
     unsafe fn sift_down_range(&mut self, pos: usize, end: usize) {
         // SAFETY: The caller guarantees that pos < end <= self.len().
-        let mut hole = unsafe { Hole::new(&mut self.data, pos) };
-        let mut child = 2 * hole.pos() + 1;
-
-        // Loop invariant: child == 2 * hole.pos() + 1.
-        while child <= end.saturating_sub(2) {
-            // compare with the greater of the two children
-            // SAFETY: child < end - 1 < self.len() and
-            //  child + 1 < end <= self.len(), so they're valid indexes.
-            //  child == 2 * hole.pos() + 1 != hole.pos() and
-            //  child + 1 == 2 * hole.pos() + 2 != hole.pos().
-            // FIXME: 2 * hole.pos() + 1 or 2 * hole.pos() + 2 could overflow
-            //  if T is a ZST
-            child += unsafe { hole.get(child) <= hole.get(child + 1) } as usize;
-
-            // if we are already in order, stop.
-            // SAFETY: child is now either the old child or the old child+1
-            //  We already proven that both are < self.len() and != hole.pos()
-            if hole.element() >= unsafe { hole.get(child) } {
-                return;
-            }
-
-            // SAFETY: same as above.
-            unsafe { hole.move_to(child) };
-            child = 2 * hole.pos() + 1;
-        }
-
-        // SAFETY: && short circuit, which means that in the
-        //  second condition it's already true that child == end - 1 < self.len().
-        if child == end - 1 && hole.element() < unsafe { hole.get(child) } {
-            // SAFETY: child is already proven to be a valid index and
-            //  child == 2 * hole.pos() + 1 != hole.pos().
-            unsafe { hole.move_to(child) };
-        }
+        unsafe {
+            sift_down_range::<D, _, _, _>(
+                self.data.as_mut_slice(),
+                &self.cmp,
+                &mut NoTrack,
+                pos,
+                end,
+            )
+        };
     }
 
     /// # Safety
@@ -614,54 +985,6 @@ impl<T: Ord> BinaryHeap<T> {
         unsafe { self.sift_down_range(pos, len) };
     }
 
-    /// Take an element at `pos` and move it all the way down the heap,
-    /// then sift it up to its position.
-    ///
-    /// Note: This is faster when the element is known to be large / should
-    /// be closer to the bottom.
-    ///
-    /// # Safety
-    ///
-    /// The caller must guarantee that `pos < self.len()`.
-    unsafe fn sift_down_to_bottom(&mut self, mut pos: usize) {
-        let end = self.len();
-        let start = pos;
-
-        // SAFETY: The caller guarantees that pos < self.len().
-        let mut hole = unsafe { Hole::new(&mut self.data, pos) };
-        let mut child = 2 * hole.pos() + 1;
-
-        // Loop invariant: child == 2 * hole.pos() + 1.
-        // ---
-        // FIXME: try inserting a bug here
-        // ---
-        while child <= end.saturating_sub(2) {
-            // SAFETY: child < end - 1 < self.len() and
-            //  child + 1 < end <= self.len(), so they're valid indexes.
-            //  child == 2 * hole.pos() + 1 != hole.pos() and
-            //  child + 1 == 2 * hole.pos() + 2 != hole.pos().
-            // FIXME: 2 * hole.pos() + 1 or 2 * hole.pos() + 2 could overflow
-            //  if T is a ZST
-            child += unsafe { hole.get(child) <= hole.get(child + 1) } as usize;
-
-            // SAFETY: Same as above
-            unsafe { hole.move_to(child) };
-            child = 2 * hole.pos() + 1;
-        }
-
-        if child == end - 1 {
-            // SAFETY: child == end - 1 < self.len(), so it's a valid index
-            //  and child == 2 * hole.pos() + 1 != hole.pos().
-            unsafe { hole.move_to(child) };
-        }
-        pos = hole.pos();
-        drop(hole);
-
-        // SAFETY: pos is the position in the hole and was already proven
-        //  to be a valid index.
-        unsafe { self.sift_up(start, pos) };
-    }
-
     /// Rebuild assuming data[0..start] is still a proper heap.
     fn rebuild_tail(&mut self, start: usize) {
         if start == self.len() {
@@ -700,153 +1023,397 @@ impl<T: Ord> BinaryHeap<T> {
     }
 
     fn rebuild(&mut self) {
-        let mut n = self.len() / 2;
-        while n > 0 {
-            n -= 1;
-            // SAFETY: n starts from self.len() / 2 and goes down to 0.
-            //  The only case when !(n < self.len()) is if
-            //  self.len() == 0, but it's ruled out by the loop condition.
-            unsafe { self.sift_down(n) };
+        make_heap_with::<D, _, _>(self.data.as_mut_slice(), &self.cmp);
+    }
+
+    /// Clears the binary heap, returning an iterator over the removed elements
+    /// in heap order. If the iterator is dropped before being fully consumed,
+    /// it drops the remaining elements in heap order.
+    ///
+    /// The returned iterator keeps a mutable borrow on the heap to optimize
+    /// its implementation.
+    ///
+    /// Note:
+    /// * `.drain_sorted()` is *O*(*n* \* log(*n*)); much slower than `.drain()`.
+    ///   You should use the latter for most cases.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from([1, 2, 3, 4, 5]);
+    /// assert_eq!(heap.len(), 5);
+    ///
+    /// drop(heap.drain_sorted()); // removes all elements in heap order
+    /// assert_eq!(heap.len(), 0);
+    /// ```
+    #[inline]
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, D, C, S> {
+        DrainSorted { inner: self }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all elements `e` for which `f(&e)` returns
+    /// `false`. The elements are visited in unsorted (and unspecified) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from([-10, -5, 1, 2, 4, 13]);
+    ///
+    /// heap.retain(|x| x % 2 == 0); // only keep even numbers
+    ///
+    /// assert_eq!(heap.into_sorted_vec(), [-10, 2, 4])
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        // Rebuild the heap even if `f` or a destructor panics, since removed
+        // elements have already been replaced by ones from the end.
+        struct RebuildOnDrop<'a, T, const D: usize, C: Compare<T>, S: HeapStorage<T>> {
+            heap: &'a mut DaryHeap<T, D, C, S>,
+            first_removed: usize,
         }
+
+        impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Drop for RebuildOnDrop<'_, T, D, C, S> {
+            fn drop(&mut self) {
+                // data[0..first_removed] is untouched, so we only need to
+                // rebuild the tail:
+                self.heap.rebuild_tail(self.first_removed);
+            }
+        }
+
+        let first_removed = self.len();
+        let mut guard = RebuildOnDrop {
+            heap: self,
+            first_removed,
+        };
+        let mut i = 0;
+        while i < guard.heap.len() {
+            if f(&guard.heap.data.as_slice()[i]) {
+                i += 1;
+            } else {
+                guard.first_removed = guard.first_removed.min(i);
+                guard.heap.data.swap_remove(i);
+            }
+        }
+        drop(guard);
+        self.debug_check_invariants();
     }
 
-    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    /// Checks that no element of the heap compares greater than its parent,
+    /// which is what makes it a heap.
+    ///
+    /// The heap keeps this up on its own, so a violation means that an item's
+    /// ordering changed while it was in the heap, that the comparator isn't a
+    /// total order, or that a comparison panicked halfway through an operation.
+    /// With the `debug-invariants` feature enabled, every method that changes
+    /// the heap runs this check when it returns, and panics if it fails.
+    /// Changes made through [`PeekMut`] are checked by the next such method.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first violation found, scanning the children in index
+    /// order.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::{BinaryHeap, HeapViolation};
+    /// use std::cell::Cell;
+    ///
+    /// let mut heap = BinaryHeap::new_by_key(|item: &Cell<i32>| item.get());
+    /// heap.extend([1, 5, 3].map(Cell::new));
+    /// assert_eq!(heap.check_invariants(), Ok(()));
+    ///
+    /// // Changing the greatest item while it's in the heap is a logic error.
+    /// heap.peek().unwrap().set(0);
+    /// assert_eq!(
+    ///     heap.check_invariants(),
+    ///     Err(HeapViolation { parent: 0, child: 1 })
+    /// );
+    /// ```
     ///
-    /// let mut a = BinaryHeap::from([-10, 1, 2, 3, 3]);
-    /// let mut b = BinaryHeap::from([-20, 5, 43]);
+    /// # Time complexity
     ///
-    /// a.append(&mut b);
+    /// The check compares every element with its parent, so it takes *O*(*n*)
+    /// time.
+    pub fn check_invariants(&self) -> Result<(), HeapViolation> {
+        let data = self.data.as_slice();
+        for child in 1..data.len() {
+            let parent = (child - 1) / D;
+            if self.cmp.compare(&data[child], &data[parent]) == Ordering::Greater {
+                return Err(HeapViolation { parent, child });
+            }
+        }
+        Ok(())
+    }
+
+    /// Panics if [`check_invariants`](Self::check_invariants) fails, when the
+    /// `debug-invariants` feature is enabled. Does nothing otherwise.
+    ///
+    /// This is only called at the end of a method that returns normally. A
+    /// panic halfway through an operation may leave the heap out of order, and
+    /// destructors like those of `PeekMut` and `DrainSorted` may run while
+    /// unwinding from it, where panicking again would abort the process.
+    #[inline]
+    fn debug_check_invariants(&self) {
+        #[cfg(feature = "debug-invariants")]
+        if let Err(violation) = self.check_invariants() {
+            panic!("{violation}");
+        }
+    }
+}
+
+impl<T, const D: usize, C, S: HeapStorage<T>> DaryHeap<T, D, C, S> {
+    /// Returns an iterator visiting all values in the underlying vector, in
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
     ///
-    /// assert_eq!(a.into_sorted_vec(), [-20, -10, 1, 2, 3, 3, 5, 43]);
-    /// assert!(b.is_empty());
     /// ```
-    pub fn append(&mut self, other: &mut Self) {
-        if self.len() < other.len() {
-            swap(self, other);
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let heap = BinaryHeap::from([1, 2, 3, 4]);
+    ///
+    /// // Print 1, 2, 3, 4 in arbitrary order
+    /// for x in heap.iter() {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.data.as_slice().iter(),
         }
+    }
 
-        let start = self.data.len();
+    /// Returns an iterator which retrieves elements in heap order.
+    /// This method consumes the original heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let heap = BinaryHeap::from([1, 2, 3, 4, 5]);
+    ///
+    /// assert_eq!(heap.into_iter_sorted().take(2).collect::<Vec<_>>(), [5, 4]);
+    /// ```
+    pub fn into_iter_sorted(self) -> IntoIterSorted<T, D, C, S> {
+        IntoIterSorted { inner: self }
+    }
 
-        self.data.append(&mut other.data);
+    /// Returns the greatest item in the binary heap, or `None` if it is empty.
+    ///
+    /// The greatest item is the one that compares greatest according to the
+    /// heap's comparator.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    ///
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.data.as_slice().first()
+    }
 
-        self.rebuild_tail(start);
+    /// Returns a reference to the comparator that orders the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{BinaryHeap, Compare};
+    /// use std::cmp::Ordering;
+    ///
+    /// let heap = BinaryHeap::<i32, _>::new_min();
+    /// assert_eq!(heap.comparator().compare(&1, &2), Ordering::Greater);
+    /// ```
+    #[must_use]
+    pub fn comparator(&self) -> &C {
+        &self.cmp
     }
 
-    /// Clears the binary heap, returning an iterator over the removed elements
-    /// in heap order. If the iterator is dropped before being fully consumed,
-    /// it drops the remaining elements in heap order.
+    /// Reserves capacity for at least `additional` more elements to be inserted in the
+    /// `BinaryHeap`. The collection may reserve more space to avoid frequent reallocations.
+    /// Storage with a fixed capacity ignores this.
     ///
-    /// The returned iterator keeps a mutable borrow on the heap to optimize
-    /// its implementation.
+    /// # Panics
     ///
-    /// Note:
-    /// * `.drain_sorted()` is *O*(*n* \* log(*n*)); much slower than `.drain()`.
-    ///   You should use the latter for most cases.
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::new();
+    /// heap.reserve(100);
+    /// assert!(heap.capacity() >= 100);
+    /// heap.push(4);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    /// Returns a slice of all values in the underlying vector, in arbitrary
+    /// order.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
-    #[inline]
-    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T> {
-        DrainSorted { inner: self }
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// use std::io::{self, Write};
+    ///
+    /// let heap = BinaryHeap::from([1, 2, 3, 4, 5, 6, 7]);
+    ///
+    /// io::sink().write(heap.as_slice()).unwrap();
+    /// ```
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_slice()
     }
 
-    /// Retains only the elements specified by the predicate.
-    ///
-    /// In other words, remove all elements `e` for which `f(&e)` returns
-    /// `false`. The elements are visited in unsorted (and unspecified) order.
+    /// Returns a reference to the underlying storage, whose elements are in
+    /// heap order.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
-    pub fn retain<F>(&mut self, mut f: F)
-    where
-        F: FnMut(&T) -> bool,
-    {
-        let mut first_removed = self.len();
-        let mut i = 0;
-        self.data.retain(|e| {
-            let keep = f(e);
-            if !keep && i < first_removed {
-                first_removed = i;
-            }
-            i += 1;
-            keep
-        });
-        // data[0..first_removed] is untouched, so we only need to rebuild the tail:
-        self.rebuild_tail(first_removed);
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let heap = BinaryHeap::from([1, 2, 3]);
+    ///
+    /// assert!(heap.as_storage().capacity() >= 3);
+    /// ```
+    #[must_use]
+    pub fn as_storage(&self) -> &S {
+        &self.data
     }
-}
 
-impl<T> BinaryHeap<T> {
-    /// Returns an iterator visiting all values in the underlying vector, in
-    /// arbitrary order.
+    /// Consumes the heap and returns the underlying storage, whose elements
+    /// are in heap order.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
-    /// let heap = BinaryHeap::from([1, 2, 3, 4]);
+    /// use proptest_binary_heap_example::binary_heap::storage::{ArrayStorage, HeapStorage};
+    /// use proptest_binary_heap_example::{BinaryHeap, MaxComparator};
     ///
-    /// // Print 1, 2, 3, 4 in arbitrary order
-    /// for x in heap.iter() {
-    ///     println!("{x}");
-    /// }
+    /// let mut heap = BinaryHeap::from_storage(ArrayStorage::<_, 4>::new(), MaxComparator);
+    /// heap.extend([1, 3, 2]);
+    ///
+    /// let storage = heap.into_storage();
+    /// assert_eq!(storage.as_slice()[0], 3);
     /// ```
-    pub fn iter(&self) -> Iter<'_, T> {
-        Iter {
-            iter: self.data.iter(),
-        }
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_storage(self) -> S {
+        self.data
     }
 
-    /// Returns an iterator which retrieves elements in heap order.
-    /// This method consumes the original heap.
+    /// Consumes the heap and returns the underlying storage along with the
+    /// comparator.
+    pub(crate) fn into_parts(self) -> (S, C) {
+        (self.data, self.cmp)
+    }
+
+    /// Returns the length of the binary heap.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
-    pub fn into_iter_sorted(self) -> IntoIterSorted<T> {
-        IntoIterSorted { inner: self }
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let heap = BinaryHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.as_slice().len()
     }
 
-    /// Returns the greatest item in the binary heap, or `None` if it is empty.
+    /// Checks if the binary heap is empty.
     ///
     /// # Examples
     ///
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::new();
-    /// assert_eq!(heap.peek(), None);
     ///
-    /// heap.push(1);
+    /// assert!(heap.is_empty());
+    ///
+    /// heap.push(3);
     /// heap.push(5);
-    /// heap.push(2);
-    /// assert_eq!(heap.peek(), Some(&5));
+    /// heap.push(1);
     ///
+    /// assert!(!heap.is_empty());
     /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all items from the binary heap.
     ///
-    /// # Time complexity
+    /// # Examples
     ///
-    /// Cost is *O*(1) in the worst case.
-    #[must_use]
-    pub fn peek(&self) -> Option<&T> {
-        self.data.get(0)
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// let mut heap = BinaryHeap::from([1, 3]);
+    ///
+    /// assert!(!heap.is_empty());
+    ///
+    /// heap.clear();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.data.clear();
     }
+}
 
+impl<T, const D: usize, C> DaryHeap<T, D, C> {
     /// Returns the number of elements the binary heap can hold without reallocating.
     ///
     /// # Examples
@@ -854,7 +1421,7 @@ impl<T> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::with_capacity(100);
     /// assert!(heap.capacity() >= 100);
     /// heap.push(4);
@@ -880,7 +1447,7 @@ impl<T> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::new();
     /// heap.reserve_exact(100);
     /// assert!(heap.capacity() >= 100);
@@ -892,28 +1459,6 @@ impl<T> BinaryHeap<T> {
         self.data.reserve_exact(additional);
     }
 
-    /// Reserves capacity for at least `additional` more elements to be inserted in the
-    /// `BinaryHeap`. The collection may reserve more space to avoid frequent reallocations.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the new capacity overflows `usize`.
-    ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    /// ```
-    /// use std::collections::BinaryHeap;
-    /// let mut heap = BinaryHeap::new();
-    /// heap.reserve(100);
-    /// assert!(heap.capacity() >= 100);
-    /// heap.push(4);
-    /// ```
-    pub fn reserve(&mut self, additional: usize) {
-        self.data.reserve(additional);
-    }
-
     /// Tries to reserve the minimum capacity for exactly `additional`
     /// elements to be inserted in the given `BinaryHeap<T>`. After calling
     /// `try_reserve_exact`, capacity will be greater than or equal to
@@ -933,6 +1478,23 @@ impl<T> BinaryHeap<T> {
     ///
     /// # Examples
     ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// use std::collections::TryReserveError;
+    ///
+    /// fn find_max_slow(data: &[u32]) -> Result<Option<u32>, TryReserveError> {
+    ///     let mut heap = BinaryHeap::new();
+    ///
+    ///     // Pre-reserve the memory, exiting if we can't
+    ///     heap.try_reserve_exact(data.len())?;
+    ///
+    ///     // Now we know this can't OOM in the middle of our complex work
+    ///     heap.extend(data.iter());
+    ///
+    ///     Ok(heap.pop())
+    /// }
+    /// # find_max_slow(&[1, 2, 3]).expect("why is the test harness OOMing on 12 bytes?");
+    /// ```
     pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
         self.data.try_reserve_exact(additional)
     }
@@ -950,6 +1512,23 @@ impl<T> BinaryHeap<T> {
     ///
     /// # Examples
     ///
+    /// ```
+    /// use proptest_binary_heap_example::BinaryHeap;
+    /// use std::collections::TryReserveError;
+    ///
+    /// fn find_max_slow(data: &[u32]) -> Result<Option<u32>, TryReserveError> {
+    ///     let mut heap = BinaryHeap::new();
+    ///
+    ///     // Pre-reserve the memory, exiting if we can't
+    ///     heap.try_reserve(data.len())?;
+    ///
+    ///     // Now we know this can't OOM in the middle of our complex work
+    ///     heap.extend(data.iter());
+    ///
+    ///     Ok(heap.pop())
+    /// }
+    /// # find_max_slow(&[1, 2, 3]).expect("why is the test harness OOMing on 12 bytes?");
+    /// ```
     pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
         self.data.try_reserve(additional)
     }
@@ -961,7 +1540,7 @@ impl<T> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap: BinaryHeap<i32> = BinaryHeap::with_capacity(100);
     ///
     /// assert!(heap.capacity() >= 100);
@@ -982,7 +1561,7 @@ impl<T> BinaryHeap<T> {
     /// # Examples
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap: BinaryHeap<i32> = BinaryHeap::with_capacity(100);
     ///
     /// assert!(heap.capacity() >= 100);
@@ -994,18 +1573,6 @@ impl<T> BinaryHeap<T> {
         self.data.shrink_to(min_capacity)
     }
 
-    /// Returns a slice of all values in the underlying vector, in arbitrary
-    /// order.
-    ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    #[must_use]
-    pub fn as_slice(&self) -> &[T] {
-        self.data.as_slice()
-    }
-
     /// Consumes the `BinaryHeap` and returns the underlying vector
     /// in arbitrary order.
     ///
@@ -1014,7 +1581,7 @@ impl<T> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let heap = BinaryHeap::from([1, 2, 3, 4, 5, 6, 7]);
     /// let vec = heap.into_vec();
     ///
@@ -1028,46 +1595,6 @@ impl<T> BinaryHeap<T> {
         self.into()
     }
 
-    /// Returns the length of the binary heap.
-    ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    /// ```
-    /// use std::collections::BinaryHeap;
-    /// let heap = BinaryHeap::from([1, 3]);
-    ///
-    /// assert_eq!(heap.len(), 2);
-    /// ```
-    #[must_use]
-    pub fn len(&self) -> usize {
-        self.data.len()
-    }
-
-    /// Checks if the binary heap is empty.
-    ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    /// ```
-    /// use std::collections::BinaryHeap;
-    /// let mut heap = BinaryHeap::new();
-    ///
-    /// assert!(heap.is_empty());
-    ///
-    /// heap.push(3);
-    /// heap.push(5);
-    /// heap.push(1);
-    ///
-    /// assert!(!heap.is_empty());
-    /// ```
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
-
     /// Clears the binary heap, returning an iterator over the removed elements
     /// in arbitrary order. If the iterator is dropped before being fully
     /// consumed, it drops the remaining elements in arbitrary order.
@@ -1080,7 +1607,7 @@ impl<T> BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let mut heap = BinaryHeap::from([1, 3]);
     ///
     /// assert!(!heap.is_empty());
@@ -1097,49 +1624,229 @@ impl<T> BinaryHeap<T> {
             iter: self.data.drain(..),
         }
     }
+}
 
-    /// Drops all items from the binary heap.
-    ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    /// ```
-    /// use std::collections::BinaryHeap;
-    /// let mut heap = BinaryHeap::from([1, 3]);
-    ///
-    /// assert!(!heap.is_empty());
-    ///
-    /// heap.clear();
-    ///
-    /// assert!(heap.is_empty());
-    /// ```
-    pub fn clear(&mut self) {
-        self.drain();
+// The implementations of sift_up and sift_down use unsafe blocks in
+// order to move an element out of the vector (leaving behind a
+// hole), shift along the others and move the removed element back into the
+// vector at the final location of the hole.
+// The `Hole` type is used to represent this, and make sure
+// the hole is filled back at the end of its scope, even on panic.
+// Using a hole reduces the constant factor compared to using swaps,
+// which involves twice as many moves.
+//
+// These are free functions rather than methods so that heaps which need to
+// know where each element ends up (such as `IndexedHeap`) can share them,
+// passing a `Track` implementation that is told about every move.
+
+/// # Safety
+///
+/// The caller must guarantee that `pos < data.len()`.
+pub(crate) unsafe fn sift_up<const D: usize, T, C, K>(
+    data: &mut [T],
+    cmp: &C,
+    tracker: &mut K,
+    start: usize,
+    pos: usize,
+) -> usize
+where
+    C: Compare<T>,
+    K: Track<T>,
+{
+    // Take out the value at `pos` and create a hole.
+    // SAFETY: The caller guarantees that pos < data.len()
+    let mut hole = unsafe { Hole::new(data, tracker, pos) };
+
+    while hole.pos() > start {
+        let parent = (hole.pos() - 1) / D;
+
+        // SAFETY: hole.pos() > start >= 0, which means hole.pos() > 0
+        //  and so hole.pos() - 1 can't underflow.
+        //  This guarantees that parent < hole.pos() so
+        //  it's a valid index and also != hole.pos().
+        if cmp.compare(hole.element(), unsafe { hole.get(parent) }) != Ordering::Greater {
+            break;
+        }
+
+        // SAFETY: Same as above
+        unsafe { hole.move_to(parent) };
+    }
+
+    hole.pos()
+}
+
+/// Returns the index of the greatest of the children `first..end` of the hole.
+/// If several children are equally great, the last one is returned.
+///
+/// # Safety
+///
+/// The caller must guarantee that `first < end <= data.len()` and that
+/// `first..end` doesn't contain `hole.pos()`.
+#[inline]
+unsafe fn greatest_child<T, C, K>(hole: &Hole<'_, T, K>, cmp: &C, first: usize, end: usize) -> usize
+where
+    C: Compare<T>,
+    K: Track<T>,
+{
+    let mut greatest = first;
+    for child in first + 1..end {
+        // SAFETY: greatest and child are both in first..end, so they're valid
+        //  indexes and != hole.pos().
+        if cmp.compare(unsafe { hole.get(greatest) }, unsafe { hole.get(child) })
+            != Ordering::Greater
+        {
+            greatest = child;
+        }
+    }
+    greatest
+}
+
+/// Take an element at `pos` and move it down the heap,
+/// while its children are larger.
+///
+/// # Safety
+///
+/// The caller must guarantee that `pos < end <= data.len()`.
+pub(crate) unsafe fn sift_down_range<const D: usize, T, C, K>(
+    data: &mut [T],
+    cmp: &C,
+    tracker: &mut K,
+    pos: usize,
+    end: usize,
+) where
+    C: Compare<T>,
+    K: Track<T>,
+{
+    // SAFETY: The caller guarantees that pos < end <= data.len().
+    let mut hole = unsafe { Hole::new(data, tracker, pos) };
+    let mut child = D * hole.pos() + 1;
+
+    // Loop invariant: child == D * hole.pos() + 1.
+    while child <= end.saturating_sub(D) {
+        // compare with the greatest of the D children
+        // SAFETY: child + D - 1 < end <= data.len(), so all of the children
+        //  are valid indexes, and they're all > hole.pos().
+        // FIXME: D * hole.pos() + D could overflow if T is a ZST
+        let greatest = unsafe { greatest_child(&hole, cmp, child, child + D) };
+
+        // if we are already in order, stop.
+        // SAFETY: greatest is one of the children, which we already proved
+        //  are < data.len() and != hole.pos()
+        if cmp.compare(hole.element(), unsafe { hole.get(greatest) }) != Ordering::Less {
+            return;
+        }
+
+        // SAFETY: same as above.
+        unsafe { hole.move_to(greatest) };
+        child = D * hole.pos() + 1;
+    }
+
+    // There may be fewer than D children left at the end of the slice.
+    if child < end {
+        // SAFETY: child < end <= data.len() and child > hole.pos().
+        let greatest = unsafe { greatest_child(&hole, cmp, child, end) };
+        // SAFETY: greatest is a valid index and != hole.pos().
+        if cmp.compare(hole.element(), unsafe { hole.get(greatest) }) == Ordering::Less {
+            // SAFETY: same as above.
+            unsafe { hole.move_to(greatest) };
+        }
     }
 }
 
+/// Take an element at `pos` and move it all the way down the heap,
+/// then sift it up to its position.
+///
+/// Note: This is faster when the element is known to be large / should
+/// be closer to the bottom.
+///
+/// # Safety
+///
+/// The caller must guarantee that `pos < data.len()`.
+pub(crate) unsafe fn sift_down_to_bottom<const D: usize, T, C, K>(
+    data: &mut [T],
+    cmp: &C,
+    tracker: &mut K,
+    mut pos: usize,
+) where
+    C: Compare<T>,
+    K: Track<T>,
+{
+    let end = data.len();
+    let start = pos;
+
+    // SAFETY: The caller guarantees that pos < data.len().
+    let mut hole = unsafe { Hole::new(data, tracker, pos) };
+    let mut child = D * hole.pos() + 1;
+
+    // Loop invariant: child == D * hole.pos() + 1.
+    // ---
+    // FIXME: try inserting a bug here
+    // ---
+    while child <= end.saturating_sub(D) {
+        // SAFETY: child + D - 1 < end <= data.len(), so all of the children
+        //  are valid indexes, and they're all > hole.pos().
+        // FIXME: D * hole.pos() + D could overflow if T is a ZST
+        let greatest = unsafe { greatest_child(&hole, cmp, child, child + D) };
+
+        // SAFETY: Same as above
+        unsafe { hole.move_to(greatest) };
+        child = D * hole.pos() + 1;
+    }
+
+    if child < end {
+        // SAFETY: child < end <= data.len() and child > hole.pos().
+        let greatest = unsafe { greatest_child(&hole, cmp, child, end) };
+        // SAFETY: greatest is a valid index and != hole.pos().
+        unsafe { hole.move_to(greatest) };
+    }
+    pos = hole.pos();
+    drop(hole);
+
+    // SAFETY: pos is the position in the hole and was already proven
+    //  to be a valid index.
+    unsafe { sift_up::<D, _, _, _>(data, cmp, tracker, start, pos) };
+}
+
+/// Receives a notification whenever a `Hole` moves an element to a new position.
+pub(crate) trait Track<T> {
+    /// Called after `elt` has been written to `data[pos]`.
+    fn track(&mut self, elt: &T, pos: usize);
+}
+
+/// A `Track` implementation that ignores all moves.
+pub(crate) struct NoTrack;
+
+impl<T> Track<T> for NoTrack {
+    #[inline(always)]
+    fn track(&mut self, _elt: &T, _pos: usize) {}
+}
+
 /// Hole represents a hole in a slice i.e., an index without valid value
 /// (because it was moved from or duplicated).
 /// In drop, `Hole` will restore the slice by filling the hole
 /// position with the value that was originally removed.
-struct Hole<'a, T: 'a> {
+///
+/// Every element that is written into the slice, including the removed one
+/// when the hole is filled, is reported to the `tracker`.
+struct Hole<'a, T: 'a, K: Track<T>> {
     data: &'a mut [T],
+    tracker: &'a mut K,
     elt: ManuallyDrop<T>,
     pos: usize,
 }
 
-impl<'a, T> Hole<'a, T> {
+impl<'a, T, K: Track<T>> Hole<'a, T, K> {
     /// Create a new `Hole` at index `pos`.
     ///
     /// Unsafe because pos must be within the data slice.
     #[inline]
-    unsafe fn new(data: &'a mut [T], pos: usize) -> Self {
+    unsafe fn new(data: &'a mut [T], tracker: &'a mut K, pos: usize) -> Self {
         debug_assert!(pos < data.len());
         // SAFE: pos should be inside the slice
         let elt = unsafe { ptr::read(data.get_unchecked(pos)) };
         Hole {
             data,
+            tracker,
             elt: ManuallyDrop::new(elt),
             pos,
         }
@@ -1178,18 +1885,20 @@ impl<'a, T> Hole<'a, T> {
             let index_ptr: *const _ = ptr.add(index);
             let hole_ptr = ptr.add(self.pos);
             ptr::copy_nonoverlapping(index_ptr, hole_ptr, 1);
+            self.tracker.track(&*hole_ptr, self.pos);
         }
         self.pos = index;
     }
 }
 
-impl<T> Drop for Hole<'_, T> {
+impl<T, K: Track<T>> Drop for Hole<'_, T, K> {
     #[inline]
     fn drop(&mut self) {
         // fill the hole again
         unsafe {
             let pos = self.pos;
             ptr::copy_nonoverlapping(&*self.elt, self.data.get_unchecked_mut(pos), 1);
+            self.tracker.track(self.data.get_unchecked(pos), pos);
         }
     }
 }
@@ -1202,7 +1911,7 @@ impl<T> Drop for Hole<'_, T> {
 /// [`iter`]: BinaryHeap::iter
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 pub struct Iter<'a, T: 'a> {
-    iter: slice::Iter<'a, T>,
+    iter: core::slice::Iter<'a, T>,
 }
 
 impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
@@ -1295,13 +2004,29 @@ impl<T> ExactSizeIterator for IntoIter<T> {}
 
 impl<T> FusedIterator for IntoIter<T> {}
 
+/// An owning iterator over the elements of a `BinaryHeap`, in heap order.
+///
+/// This `struct` is created by [`BinaryHeap::into_iter_sorted()`]. See its
+/// documentation for more.
+///
+/// [`into_iter_sorted`]: BinaryHeap::into_iter_sorted
 #[must_use = "iterators are lazy and do nothing unless consumed"]
-#[derive(Clone, Debug)]
-pub struct IntoIterSorted<T> {
-    inner: BinaryHeap<T>,
+#[derive(Clone)]
+pub struct IntoIterSorted<T, const D: usize = 2, C = MaxComparator, S = Vec<T>> {
+    inner: DaryHeap<T, D, C, S>,
+}
+
+impl<T: fmt::Debug, const D: usize, C, S: HeapStorage<T>> fmt::Debug
+    for IntoIterSorted<T, D, C, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIterSorted")
+            .field("inner", &self.inner)
+            .finish()
+    }
 }
 
-impl<T: Ord> Iterator for IntoIterSorted<T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Iterator for IntoIterSorted<T, D, C, S> {
     type Item = T;
 
     #[inline]
@@ -1316,9 +2041,15 @@ impl<T: Ord> Iterator for IntoIterSorted<T> {
     }
 }
 
-impl<T: Ord> ExactSizeIterator for IntoIterSorted<T> {}
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> ExactSizeIterator
+    for IntoIterSorted<T, D, C, S>
+{
+}
 
-impl<T: Ord> FusedIterator for IntoIterSorted<T> {}
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> FusedIterator
+    for IntoIterSorted<T, D, C, S>
+{
+}
 
 /// A draining iterator over the elements of a `BinaryHeap`.
 ///
@@ -1362,23 +2093,40 @@ impl<T> FusedIterator for Drain<'_, T> {}
 /// documentation for more.
 ///
 /// [`drain_sorted`]: BinaryHeap::drain_sorted
-#[derive(Debug)]
-pub struct DrainSorted<'a, T: Ord> {
-    inner: &'a mut BinaryHeap<T>,
+pub struct DrainSorted<
+    'a,
+    T,
+    const D: usize = 2,
+    C: Compare<T> = MaxComparator,
+    S: HeapStorage<T> = Vec<T>,
+> {
+    inner: &'a mut DaryHeap<T, D, C, S>,
+}
+
+impl<T: fmt::Debug, const D: usize, C: Compare<T>, S: HeapStorage<T>> fmt::Debug
+    for DrainSorted<'_, T, D, C, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DrainSorted").field(&self.inner).finish()
+    }
 }
 
-impl<'a, T: Ord> Drop for DrainSorted<'a, T> {
+impl<'a, T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Drop for DrainSorted<'a, T, D, C, S> {
     /// Removes heap elements in heap order.
     fn drop(&mut self) {
-        struct DropGuard<'r, 'a, T: Ord>(&'r mut DrainSorted<'a, T>);
+        struct DropGuard<'r, 'a, T, const D: usize, C: Compare<T>, S: HeapStorage<T>>(
+            &'r mut DrainSorted<'a, T, D, C, S>,
+        );
 
-        impl<'r, 'a, T: Ord> Drop for DropGuard<'r, 'a, T> {
+        impl<'r, 'a, T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Drop
+            for DropGuard<'r, 'a, T, D, C, S>
+        {
             fn drop(&mut self) {
-                while self.0.inner.pop().is_some() {}
+                while self.0.inner.pop_unchecked().is_some() {}
             }
         }
 
-        while let Some(item) = self.inner.pop() {
+        while let Some(item) = self.inner.pop_unchecked() {
             let guard = DropGuard(self);
             drop(item);
             mem::forget(guard);
@@ -1386,7 +2134,7 @@ impl<'a, T: Ord> Drop for DrainSorted<'a, T> {
     }
 }
 
-impl<T: Ord> Iterator for DrainSorted<'_, T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Iterator for DrainSorted<'_, T, D, C, S> {
     type Item = T;
 
     #[inline]
@@ -1401,24 +2149,28 @@ impl<T: Ord> Iterator for DrainSorted<'_, T> {
     }
 }
 
-impl<T: Ord> ExactSizeIterator for DrainSorted<'_, T> {}
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> ExactSizeIterator
+    for DrainSorted<'_, T, D, C, S>
+{
+}
 
-impl<T: Ord> FusedIterator for DrainSorted<'_, T> {}
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> FusedIterator
+    for DrainSorted<'_, T, D, C, S>
+{
+}
 
-impl<T: Ord> From<Vec<T>> for BinaryHeap<T> {
+impl<T: Ord, const D: usize> From<Vec<T>> for DaryHeap<T, D> {
     /// Converts a `Vec<T>` into a `BinaryHeap<T>`.
     ///
     /// This conversion happens in-place, and has *O*(*n*) time complexity.
-    fn from(vec: Vec<T>) -> BinaryHeap<T> {
-        let mut heap = BinaryHeap { data: vec };
-        heap.rebuild();
-        heap
+    fn from(vec: Vec<T>) -> DaryHeap<T, D> {
+        DaryHeap::from_vec_cmp(vec, MaxComparator)
     }
 }
 
-impl<T: Ord, const N: usize> From<[T; N]> for BinaryHeap<T> {
+impl<T: Ord, const D: usize, const N: usize> From<[T; N]> for DaryHeap<T, D> {
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     ///
     /// let mut h1 = BinaryHeap::from([1, 4, 2, 3]);
     /// let mut h2: BinaryHeap<_> = [1, 4, 2, 3].into();
@@ -1431,23 +2183,23 @@ impl<T: Ord, const N: usize> From<[T; N]> for BinaryHeap<T> {
     }
 }
 
-impl<T> From<BinaryHeap<T>> for Vec<T> {
-    /// Converts a `BinaryHeap<T>` into a `Vec<T>`.
+impl<T, const D: usize, C> From<DaryHeap<T, D, C>> for Vec<T> {
+    /// Converts a `BinaryHeap<T, C>` into a `Vec<T>`.
     ///
     /// This conversion requires no data movement or allocation, and has
     /// constant time complexity.
-    fn from(heap: BinaryHeap<T>) -> Vec<T> {
+    fn from(heap: DaryHeap<T, D, C>) -> Vec<T> {
         heap.data
     }
 }
 
-impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BinaryHeap<T> {
-        BinaryHeap::from(iter.into_iter().collect::<Vec<_>>())
+impl<T: Ord, const D: usize> FromIterator<T> for DaryHeap<T, D> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> DaryHeap<T, D> {
+        DaryHeap::from(iter.into_iter().collect::<Vec<_>>())
     }
 }
 
-impl<T> IntoIterator for BinaryHeap<T> {
+impl<T, const D: usize, C> IntoIterator for DaryHeap<T, D, C> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
@@ -1460,7 +2212,7 @@ impl<T> IntoIterator for BinaryHeap<T> {
     /// Basic usage:
     ///
     /// ```
-    /// use std::collections::BinaryHeap;
+    /// use proptest_binary_heap_example::BinaryHeap;
     /// let heap = BinaryHeap::from([1, 2, 3, 4]);
     ///
     /// // Print 1, 2, 3, 4 in arbitrary order
@@ -1476,7 +2228,7 @@ impl<T> IntoIterator for BinaryHeap<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a BinaryHeap<T> {
+impl<'a, T, const D: usize, C, S: HeapStorage<T>> IntoIterator for &'a DaryHeap<T, D, C, S> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
@@ -1485,7 +2237,7 @@ impl<'a, T> IntoIterator for &'a BinaryHeap<T> {
     }
 }
 
-impl<T: Ord> Extend<T> for BinaryHeap<T> {
+impl<T, const D: usize, C: Compare<T>, S: HeapStorage<T>> Extend<T> for DaryHeap<T, D, C, S> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         let iter = iter.into_iter();
         for elem in iter {
@@ -1494,7 +2246,31 @@ impl<T: Ord> Extend<T> for BinaryHeap<T> {
     }
 }
 
-impl<'a, T: 'a + Ord + Copy> Extend<&'a T> for BinaryHeap<T> {
+impl<T, const D: usize, C: Compare<T>> PriorityQueue<T> for DaryHeap<T, D, C> {
+    fn push(&mut self, item: T) {
+        self.push(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
+
+impl<'a, T: 'a + Copy, const D: usize, C: Compare<T>, S: HeapStorage<T>> Extend<&'a T>
+    for DaryHeap<T, D, C, S>
+{
     fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
         self.extend(iter.into_iter().cloned());
     }
@@ -0,0 +1,133 @@
+//! Heaps whose storage allocates with a custom allocator, through the
+//! `allocator-api2` crate.
+
+use allocator_api2::alloc::Allocator;
+use allocator_api2::collections::TryReserveError;
+use allocator_api2::vec::Vec;
+
+use super::DaryHeap;
+use crate::compare::MaxComparator;
+
+impl<T: Ord, const D: usize, A: Allocator> DaryHeap<T, D, MaxComparator, Vec<T, A>> {
+    /// Creates an empty `BinaryHeap` as a max-heap, which allocates with
+    /// `alloc`.
+    ///
+    /// Heaps with other comparators can be created with
+    /// [`from_storage`](DaryHeap::from_storage) and
+    /// [`Vec::new_in`](allocator_api2::vec::Vec::new_in).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use allocator_api2::alloc::Global;
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new_in(Global);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new_in(alloc: A) -> Self {
+        DaryHeap::from_storage(Vec::new_in(alloc), MaxComparator)
+    }
+
+    /// Creates an empty `BinaryHeap` with a specific capacity, which
+    /// allocates with `alloc`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use allocator_api2::alloc::Global;
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::with_capacity_in(10, Global);
+    /// assert!(heap.capacity() >= 10);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        DaryHeap::from_storage(Vec::with_capacity_in(capacity, alloc), MaxComparator)
+    }
+}
+
+impl<T, const D: usize, C, A: Allocator> DaryHeap<T, D, C, Vec<T, A>> {
+    /// Returns a reference to the underlying allocator.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use allocator_api2::alloc::Global;
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::<i32, _, _>::new_in(Global);
+    /// let _: &Global = heap.allocator();
+    /// ```
+    #[must_use]
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+    /// Returns the number of elements the binary heap can hold without reallocating.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Tries to reserve the minimum capacity for exactly `additional`
+    /// elements to be inserted in the given `BinaryHeap`, using the heap's
+    /// allocator.
+    ///
+    /// See [`try_reserve_exact`](DaryHeap::try_reserve_exact) on heaps backed
+    /// by a standard `Vec`.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an error
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use allocator_api2::alloc::Global;
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new_in(Global);
+    /// heap.try_reserve_exact(3).expect("why is the test harness OOMing on 12 bytes?");
+    /// heap.extend([1, 2, 3]);
+    /// assert!(heap.try_reserve_exact(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve_exact(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements to
+    /// be inserted in the given `BinaryHeap`, using the heap's allocator.
+    ///
+    /// See [`try_reserve`](DaryHeap::try_reserve) on heaps backed by a
+    /// standard `Vec`.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an error
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use allocator_api2::alloc::Global;
+    /// use proptest_binary_heap_example::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new_in(Global);
+    /// heap.try_reserve(3).expect("why is the test harness OOMing on 12 bytes?");
+    /// heap.extend([1, 2, 3]);
+    /// assert!(heap.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve(additional)
+    }
+}
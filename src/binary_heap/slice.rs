@@ -0,0 +1,286 @@
+//! Heap algorithms that run in place on slices.
+//!
+//! These are the building blocks of [`BinaryHeap`](crate::BinaryHeap), exposed
+//! so that buffers which can't be moved into a `Vec` can be heapified, like
+//! C++'s `<algorithm>` heap functions. Each function is a max-heap with respect
+//! to the natural order of `T`, and has a `_by` variant that takes a
+//! comparison function instead.
+//!
+//! Like the rest of this crate, passing a slice that isn't a heap to a function
+//! that expects one is a logic error. The behavior resulting from such a logic
+//! error is not specified, but will not be undefined behavior.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::binary_heap::slice;
+//!
+//! let mut buf = [3, 1, 4, 1, 5, 9, 2, 6];
+//! slice::make_heap(&mut buf);
+//! assert!(slice::is_heap(&buf));
+//! assert_eq!(buf[0], 9);
+//!
+//! // Replace the greatest element with a new one.
+//! slice::pop_heap(&mut buf);
+//! buf[7] = 7;
+//! slice::push_heap(&mut buf);
+//! assert_eq!(buf[0], 7);
+//!
+//! slice::sort_heap(&mut buf);
+//! assert_eq!(buf, [1, 1, 2, 3, 4, 5, 6, 7]);
+//! ```
+
+use core::cmp::Ordering;
+
+use super::{sift_down_range, sift_down_to_bottom, sift_up, NoTrack};
+use crate::compare::{Compare, FnComparator, MaxComparator};
+
+/// Rearranges the elements of `v` so that they form a max-heap.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// let mut v = [1, 5, 2];
+/// slice::make_heap(&mut v);
+/// assert_eq!(v[0], 5);
+/// assert!(slice::is_heap(&v));
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(*n*) for a slice of length *n*.
+pub fn make_heap<T: Ord>(v: &mut [T]) {
+    make_heap_with::<2, _, _>(v, &MaxComparator);
+}
+
+/// Rearranges the elements of `v` so that they form a max-heap with respect to
+/// the comparison function `compare`.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// let mut v = [1, 5, 2];
+/// slice::make_heap_by(&mut v, |a, b| b.cmp(a));
+/// assert_eq!(v[0], 1);
+/// ```
+pub fn make_heap_by<T, F>(v: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    make_heap_with::<2, _, _>(v, &FnComparator(compare));
+}
+
+/// Moves the last element of `v` into place, so that a slice whose first
+/// `v.len() - 1` elements form a heap becomes a heap in its entirety.
+///
+/// Does nothing if `v` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// let mut v = [5, 1, 2, 8];
+/// slice::push_heap(&mut v);
+/// assert_eq!(v[0], 8);
+/// assert!(slice::is_heap(&v));
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(log(*n*)) for a slice of length *n*.
+pub fn push_heap<T: Ord>(v: &mut [T]) {
+    push_heap_with::<2, _, _>(v, &MaxComparator);
+}
+
+/// Moves the last element of `v` into place with respect to the comparison
+/// function `compare`. See [`push_heap`].
+pub fn push_heap_by<T, F>(v: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    push_heap_with::<2, _, _>(v, &FnComparator(compare));
+}
+
+/// Moves the greatest element of the heap `v` to the end of the slice, and
+/// rearranges the remaining `v.len() - 1` elements so that they form a heap.
+///
+/// Does nothing if `v` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// let mut v = [8, 5, 2, 1];
+/// slice::pop_heap(&mut v);
+/// assert_eq!(v[3], 8);
+/// assert!(slice::is_heap(&v[..3]));
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(log(*n*)) for a slice of length *n*.
+pub fn pop_heap<T: Ord>(v: &mut [T]) {
+    pop_heap_with::<2, _, _>(v, &MaxComparator);
+}
+
+/// Moves the greatest element of the heap `v` to the end of the slice with
+/// respect to the comparison function `compare`. See [`pop_heap`].
+pub fn pop_heap_by<T, F>(v: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    pop_heap_with::<2, _, _>(v, &FnComparator(compare));
+}
+
+/// Sorts the heap `v` in ascending order.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// let mut v = [4, 1, 3, 2];
+/// slice::make_heap(&mut v);
+/// slice::sort_heap(&mut v);
+/// assert_eq!(v, [1, 2, 3, 4]);
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(*n* * log(*n*)) for a slice of length *n*.
+pub fn sort_heap<T: Ord>(v: &mut [T]) {
+    sort_heap_with::<2, _, _>(v, &MaxComparator);
+}
+
+/// Sorts the heap `v` in ascending order with respect to the comparison
+/// function `compare`. See [`sort_heap`].
+pub fn sort_heap_by<T, F>(v: &mut [T], compare: F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    sort_heap_with::<2, _, _>(v, &FnComparator(compare));
+}
+
+/// Returns true if the elements of `v` form a max-heap.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// assert!(slice::is_heap(&[5, 3, 4, 1]));
+/// assert!(!slice::is_heap(&[5, 3, 4, 6]));
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(*n*) for a slice of length *n*.
+#[must_use]
+pub fn is_heap<T: Ord>(v: &[T]) -> bool {
+    is_heap_until(v) == v.len()
+}
+
+/// Returns true if the elements of `v` form a max-heap with respect to the
+/// comparison function `compare`.
+#[must_use]
+pub fn is_heap_by<T, F>(v: &[T], compare: F) -> bool
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    is_heap_until_by(v, compare) == v.len()
+}
+
+/// Returns the length of the longest prefix of `v` that forms a max-heap.
+///
+/// This is the index of the first element that is greater than its parent, or
+/// `v.len()` if there is no such element.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::slice;
+///
+/// assert_eq!(slice::is_heap_until(&[5, 3, 4, 6, 1]), 3);
+/// assert_eq!(slice::is_heap_until(&[5, 3, 4, 1]), 4);
+/// ```
+///
+/// # Time complexity
+///
+/// *O*(*n*) for a slice of length *n*.
+#[must_use]
+pub fn is_heap_until<T: Ord>(v: &[T]) -> usize {
+    is_heap_until_with::<2, _, _>(v, &MaxComparator)
+}
+
+/// Returns the length of the longest prefix of `v` that forms a max-heap with
+/// respect to the comparison function `compare`.
+#[must_use]
+pub fn is_heap_until_by<T, F>(v: &[T], compare: F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    is_heap_until_with::<2, _, _>(v, &FnComparator(compare))
+}
+
+pub(crate) fn make_heap_with<const D: usize, T, C: Compare<T>>(v: &mut [T], cmp: &C) {
+    // The number of elements that have at least one child.
+    let mut n = (v.len() + D - 2) / D;
+    while n > 0 {
+        n -= 1;
+        let len = v.len();
+        // SAFETY: n starts from (v.len() + D - 2) / D and goes down to 0.
+        //  The only case when !(n < v.len()) is if
+        //  v.len() == 0, but it's ruled out by the loop condition.
+        unsafe { sift_down_range::<D, _, _, _>(v, cmp, &mut NoTrack, n, len) };
+    }
+}
+
+pub(crate) fn push_heap_with<const D: usize, T, C: Compare<T>>(v: &mut [T], cmp: &C) {
+    if let Some(last) = v.len().checked_sub(1) {
+        // SAFETY: last = v.len() - 1 < v.len()
+        unsafe { sift_up::<D, _, _, _>(v, cmp, &mut NoTrack, 0, last) };
+    }
+}
+
+/// Moves the element at `pos` down the heap `v`, while its children are
+/// larger. Does nothing if `pos` is out of bounds.
+pub(crate) fn sift_down_with<const D: usize, T, C: Compare<T>>(v: &mut [T], cmp: &C, pos: usize) {
+    let len = v.len();
+    if pos < len {
+        // SAFETY: pos < len = v.len()
+        unsafe { sift_down_range::<D, _, _, _>(v, cmp, &mut NoTrack, pos, len) };
+    }
+}
+
+pub(crate) fn pop_heap_with<const D: usize, T, C: Compare<T>>(v: &mut [T], cmp: &C) {
+    if v.len() > 1 {
+        let end = v.len() - 1;
+        v.swap(0, end);
+        // SAFETY: 0 < end, so the heap in front of it is not empty.
+        unsafe { sift_down_to_bottom::<D, _, _, _>(&mut v[..end], cmp, &mut NoTrack, 0) };
+    }
+}
+
+pub(crate) fn sort_heap_with<const D: usize, T, C: Compare<T>>(v: &mut [T], cmp: &C) {
+    let mut end = v.len();
+    while end > 1 {
+        end -= 1;
+        v.swap(0, end);
+        // SAFETY: `end` goes from `v.len() - 1` to 1 (both included) so:
+        //  0 < 1 <= end <= v.len() - 1 < v.len()
+        //  Which means 0 < end and end < v.len().
+        unsafe { sift_down_range::<D, _, _, _>(v, cmp, &mut NoTrack, 0, end) };
+    }
+}
+
+pub(crate) fn is_heap_until_with<const D: usize, T, C: Compare<T>>(v: &[T], cmp: &C) -> usize {
+    (1..v.len())
+        .find(|&i| cmp.compare(&v[(i - 1) / D], &v[i]) == Ordering::Less)
+        .unwrap_or(v.len())
+}
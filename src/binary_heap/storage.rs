@@ -0,0 +1,534 @@
+//! Buffers that a [`DaryHeap`](super::DaryHeap) can keep its elements in.
+//!
+//! A heap is generic over its storage through the [`HeapStorage`] trait, and
+//! uses a `Vec<T>` unless told otherwise. This module provides a few
+//! alternatives:
+//!
+//! * [`ArrayStorage`], a fixed-capacity inline array that never allocates.
+//! * [`SmallStorage`], an inline array that moves its elements into a `Vec`
+//!   once it outgrows its capacity.
+//! * `&mut Vec<T>`, which lets a heap borrow a vector owned by someone else,
+//!   and leaves the elements in it when the heap goes away.
+//! * `allocator_api2::vec::Vec<T, A>`, which allocates with a custom
+//!   allocator, with the `allocator-api2` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::binary_heap::storage::SmallStorage;
+//! use proptest_binary_heap_example::{BinaryHeap, MaxComparator};
+//!
+//! let mut heap = BinaryHeap::from_storage(SmallStorage::<_, 4>::new(), MaxComparator);
+//! heap.extend([3, 1, 4]);
+//! assert!(!heap.into_storage().is_spilled());
+//!
+//! let mut buf = vec![2, 7, 1];
+//! let mut heap = BinaryHeap::from_storage(&mut buf, MaxComparator);
+//! heap.push(8);
+//! assert_eq!(heap.pop(), Some(8));
+//! assert_eq!(heap.pop(), Some(7));
+//! drop(heap);
+//! assert_eq!(buf.len(), 2);
+//! ```
+
+use core::fmt;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::slice;
+
+use alloc::vec::Vec;
+
+/// A contiguous buffer that a heap keeps its elements in.
+///
+/// The heap only ever reorders the elements through [`as_mut_slice`], and
+/// grows and shrinks the buffer at its end, so the storage behaves like a
+/// stack of elements with random access.
+///
+/// [`as_mut_slice`]: HeapStorage::as_mut_slice
+///
+/// # Safety
+///
+/// The heap uses unchecked indexing based on the length of the storage, so
+/// implementations must uphold the following:
+///
+/// * [`as_slice`](HeapStorage::as_slice) and
+///   [`as_mut_slice`](HeapStorage::as_mut_slice) return the same elements,
+///   and their length only changes through the methods of this trait.
+/// * [`push`](HeapStorage::push) either appends exactly one element or
+///   panics, and [`pop`](HeapStorage::pop) removes exactly the last element,
+///   or returns `None` if there is none.
+/// * [`swap_remove`](HeapStorage::swap_remove) behaves like
+///   [`Vec::swap_remove`].
+/// * [`reserve`](HeapStorage::reserve) and
+///   [`clear`](HeapStorage::clear) never change the elements, other than
+///   `clear` removing all of them.
+pub unsafe trait HeapStorage<T> {
+    /// Returns the elements of the storage.
+    fn as_slice(&self) -> &[T];
+
+    /// Returns the elements of the storage mutably.
+    fn as_mut_slice(&mut self) -> &mut [T];
+
+    /// Appends an element to the end of the storage.
+    ///
+    /// # Panics
+    ///
+    /// Storage with a fixed capacity panics if it is full.
+    fn push(&mut self, item: T);
+
+    /// Removes the last element of the storage and returns it, or `None` if
+    /// it is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Removes the element at `index` and returns it, replacing it with the
+    /// last element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn swap_remove(&mut self, index: usize) -> T;
+
+    /// Reserves capacity for at least `additional` more elements, if the
+    /// storage can grow. Storage with a fixed capacity ignores this.
+    fn reserve(&mut self, additional: usize);
+
+    /// Removes all elements from the storage.
+    fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+// SAFETY: every method forwards to the `Vec` method of the same name.
+unsafe impl<T> HeapStorage<T> for Vec<T> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        Vec::push(self, item);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        Vec::swap_remove(self, index)
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+}
+
+// SAFETY: every method forwards to the `Vec` method of the same name.
+unsafe impl<T> HeapStorage<T> for &mut Vec<T> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        Vec::push(self, item);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        Vec::swap_remove(self, index)
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+}
+
+// SAFETY: every method forwards to the `Vec` method of the same name.
+#[cfg(feature = "allocator-api2")]
+unsafe impl<T, A: allocator_api2::alloc::Allocator> HeapStorage<T>
+    for allocator_api2::vec::Vec<T, A>
+{
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        allocator_api2::vec::Vec::push(self, item);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        allocator_api2::vec::Vec::pop(self)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        allocator_api2::vec::Vec::swap_remove(self, index)
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        allocator_api2::vec::Vec::reserve(self, additional);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        allocator_api2::vec::Vec::clear(self);
+    }
+}
+
+/// Storage for at most `N` elements in an inline array.
+///
+/// Pushing onto a full `ArrayStorage` panics. Unlike
+/// [`ArrayHeap`](crate::ArrayHeap), which reports a full heap through
+/// [`ArrayHeap::try_push`](crate::ArrayHeap::try_push), this is meant for
+/// heaps whose size is known to be bounded up front.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::storage::ArrayStorage;
+/// use proptest_binary_heap_example::{BinaryHeap, MinComparator};
+///
+/// let mut heap = BinaryHeap::from_storage(ArrayStorage::<_, 3>::new(), MinComparator);
+/// heap.extend([5, 2, 8]);
+/// assert_eq!(heap.pop(), Some(2));
+/// ```
+pub struct ArrayStorage<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayStorage<T, N> {
+    /// Creates an empty `ArrayStorage`.
+    #[must_use]
+    pub const fn new() -> Self {
+        ArrayStorage {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements the storage can hold, which is `N`.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends an element to the end of the storage, or returns it if the
+    /// storage is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(item)` if the storage already holds `N` elements.
+    pub fn try_push(&mut self, item: T) -> Result<(), T> {
+        match self.data.get_mut(self.len) {
+            Some(slot) => {
+                slot.write(item);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(item),
+        }
+    }
+
+    /// Moves the elements into a `Vec` with room for at least `capacity`
+    /// elements, leaving `self` empty.
+    fn spill(&mut self, capacity: usize) -> Vec<T> {
+        let len = mem::take(&mut self.len);
+        let mut vec = Vec::with_capacity(capacity.max(len));
+        // SAFETY: the first `len` elements are initialized, and setting the
+        //  length to 0 first hands their ownership over to `vec`, which has
+        //  room for at least `len` elements.
+        unsafe {
+            ptr::copy_nonoverlapping(self.data.as_ptr().cast::<T>(), vec.as_mut_ptr(), len);
+            vec.set_len(len);
+        }
+        vec
+    }
+}
+
+// SAFETY: `as_slice` and `as_mut_slice` both cover the first `len` elements,
+//  which only changes in `push`, `pop` and `swap_remove`.
+unsafe impl<T, const N: usize> HeapStorage<T> for ArrayStorage<T, N> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.data.as_ptr().cast::<T>(), self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr().cast::<T>(), self.len) }
+    }
+
+    fn push(&mut self, item: T) {
+        if self.try_push(item).is_err() {
+            panic!("ArrayStorage is full (capacity is {N})");
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        // SAFETY: the element at the old `len - 1` was initialized, and is no
+        //  longer part of the storage after decrementing the length.
+        Some(unsafe { self.data[self.len].assume_init_read() })
+    }
+
+    fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+        self.as_mut_slice().swap(index, len - 1);
+        // The storage is not empty, so this always succeeds.
+        self.pop().unwrap()
+    }
+
+    #[inline]
+    fn reserve(&mut self, _additional: usize) {}
+
+    fn clear(&mut self) {
+        let len = mem::take(&mut self.len);
+        // SAFETY: the first `len` elements are initialized, and setting the
+        //  length to 0 first means they can't be dropped twice.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.data.as_mut_ptr().cast::<T>(),
+                len,
+            ))
+        };
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayStorage<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayStorage<T, N> {
+    fn clone(&self) -> Self {
+        let mut clone = ArrayStorage::new();
+        for item in self.as_slice() {
+            // Pushing increments the length as we go, so that already cloned
+            // elements are dropped if a later clone panics.
+            clone.push(item.clone());
+        }
+        clone
+    }
+}
+
+impl<T, const N: usize> Default for ArrayStorage<T, N> {
+    fn default() -> Self {
+        ArrayStorage::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayStorage<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// Storage that keeps up to `N` elements in an inline array, and moves them
+/// into a `Vec` once it outgrows it.
+///
+/// Once spilled, the elements stay on the heap even if the storage shrinks
+/// back below `N` elements.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::binary_heap::storage::SmallStorage;
+/// use proptest_binary_heap_example::{BinaryHeap, MaxComparator};
+///
+/// let mut heap: BinaryHeap<i32, MaxComparator, SmallStorage<i32, 2>> = BinaryHeap::default();
+/// heap.push(1);
+/// heap.push(2);
+/// assert!(!heap.as_storage().is_spilled());
+/// heap.push(3);
+/// assert!(heap.as_storage().is_spilled());
+/// assert_eq!(heap.pop(), Some(3));
+/// ```
+pub struct SmallStorage<T, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+enum Repr<T, const N: usize> {
+    Inline(ArrayStorage<T, N>),
+    Spilled(Vec<T>),
+}
+
+impl<T, const N: usize> SmallStorage<T, N> {
+    /// Creates an empty `SmallStorage`, which doesn't allocate until more
+    /// than `N` elements are pushed.
+    #[must_use]
+    pub const fn new() -> Self {
+        SmallStorage {
+            repr: Repr::Inline(ArrayStorage::new()),
+        }
+    }
+
+    /// Returns true if the elements have been moved into a `Vec`.
+    #[must_use]
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.repr, Repr::Spilled(_))
+    }
+
+    /// Returns the number of elements the storage can hold without
+    /// reallocating.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(inline) => inline.capacity(),
+            Repr::Spilled(vec) => vec.capacity(),
+        }
+    }
+
+    /// Returns the `Vec` that the elements are kept in, moving them into a
+    /// new one with room for at least `capacity` elements if they are still
+    /// inline.
+    fn spilled(&mut self, capacity: usize) -> &mut Vec<T> {
+        if let Repr::Inline(inline) = &mut self.repr {
+            self.repr = Repr::Spilled(inline.spill(capacity));
+        }
+        match &mut self.repr {
+            Repr::Spilled(vec) => vec,
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+}
+
+// SAFETY: every method forwards to either `ArrayStorage` or `Vec`, and
+//  spilling moves the elements over in the same order.
+unsafe impl<T, const N: usize> HeapStorage<T> for SmallStorage<T, N> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        match &self.repr {
+            Repr::Inline(inline) => inline.as_slice(),
+            Repr::Spilled(vec) => vec,
+        }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.as_mut_slice(),
+            Repr::Spilled(vec) => vec,
+        }
+    }
+
+    fn push(&mut self, item: T) {
+        let item = match &mut self.repr {
+            Repr::Inline(inline) => match inline.try_push(item) {
+                Ok(()) => return,
+                Err(item) => item,
+            },
+            Repr::Spilled(vec) => return vec.push(item),
+        };
+        // Double the capacity when spilling, like `Vec` does when it grows.
+        self.spilled(2 * N.max(1)).push(item);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.pop(),
+            Repr::Spilled(vec) => vec.pop(),
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.swap_remove(index),
+            Repr::Spilled(vec) => vec.swap_remove(index),
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        match &mut self.repr {
+            Repr::Inline(inline) => {
+                let needed = inline
+                    .len
+                    .checked_add(additional)
+                    .expect("capacity overflow");
+                if needed > N {
+                    self.spilled(needed);
+                }
+            }
+            Repr::Spilled(vec) => vec.reserve(additional),
+        }
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.clear(),
+            Repr::Spilled(vec) => vec.clear(),
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallStorage<T, N> {
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            Repr::Inline(inline) => Repr::Inline(inline.clone()),
+            Repr::Spilled(vec) => Repr::Spilled(vec.clone()),
+        };
+        SmallStorage { repr }
+    }
+}
+
+impl<T, const N: usize> Default for SmallStorage<T, N> {
+    fn default() -> Self {
+        SmallStorage::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallStorage<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
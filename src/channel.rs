@@ -0,0 +1,727 @@
+//! An async multi-producer, multi-consumer channel that delivers messages in priority order.
+//!
+//! A channel is created with [`bounded`] or [`unbounded`], which return a [`Sender`] and a
+//! [`Receiver`]. Both halves can be cloned, and every message is received by exactly one receiver.
+//! Pending messages are kept in a [`BinaryHeap`], so [`Receiver::recv`] always yields the
+//! greatest message that has been sent but not yet received, rather than the oldest.
+//!
+//! The channel doesn't depend on any async runtime: its futures only use the [`Waker`] passed to
+//! them, so they work with any executor.
+//!
+//! * A bounded channel holds at most `capacity` messages. Once it is full,
+//!   [`Sender::send`] waits until a receiver makes room, which applies backpressure to the
+//!   senders.
+//! * Once every `Sender` has been dropped, receivers get the remaining messages, and then `None`.
+//! * Once every `Receiver` has been dropped, sending fails and hands the message back.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::channel;
+//! # use std::future::Future;
+//! # use std::pin::pin;
+//! # use std::task::{Context, Poll, Waker};
+//! # fn block_on<F: Future>(fut: F) -> F::Output {
+//! #     let mut fut = pin!(fut);
+//! #     let mut cx = Context::from_waker(Waker::noop());
+//! #     loop {
+//! #         if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+//! #             return output;
+//! #         }
+//! #     }
+//! # }
+//!
+//! let (tx, rx) = channel::bounded(8);
+//!
+//! block_on(async {
+//!     tx.send(1).await.unwrap();
+//!     tx.send(5).await.unwrap();
+//!     tx.send(3).await.unwrap();
+//!     drop(tx);
+//!
+//!     // Messages come out greatest first, and then the channel reports that it is closed.
+//!     assert_eq!(rx.recv().await, Some(5));
+//!     assert_eq!(rx.recv().await, Some(3));
+//!     assert_eq!(rx.recv().await, Some(1));
+//!     assert_eq!(rx.recv().await, None);
+//! });
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::binary_heap::BinaryHeap;
+use crate::compare::{Compare, MaxComparator};
+
+/// Creates a channel that holds at most `capacity` pending messages, as a max-heap.
+///
+/// Room for the messages is allocated as they are sent, so a large `capacity` costs nothing up
+/// front.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero, since nothing could ever be sent.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use proptest_binary_heap_example::channel::{self, TrySendError};
+/// let (tx, rx) = channel::bounded(1);
+/// tx.try_send(1).unwrap();
+/// assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+/// assert_eq!(rx.try_recv(), Ok(1));
+/// ```
+#[must_use]
+pub fn bounded<T: Ord>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    bounded_with_cmp(capacity, MaxComparator)
+}
+
+/// Creates a channel that holds any number of pending messages, as a max-heap.
+///
+/// Sending on an unbounded channel never waits.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use proptest_binary_heap_example::channel;
+/// let (tx, rx) = channel::unbounded();
+/// tx.try_send(1).unwrap();
+/// tx.try_send(2).unwrap();
+/// assert_eq!(rx.try_recv(), Ok(2));
+/// ```
+#[must_use]
+pub fn unbounded<T: Ord>() -> (Sender<T>, Receiver<T>) {
+    unbounded_with_cmp(MaxComparator)
+}
+
+/// Creates a channel that holds at most `capacity` pending messages, ordered by the comparator
+/// `cmp`.
+///
+/// Room for the messages is allocated as they are sent, so a large `capacity` costs nothing up
+/// front.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero, since nothing could ever be sent.
+#[must_use]
+pub fn bounded_with_cmp<T, C: Compare<T>>(
+    capacity: usize,
+    cmp: C,
+) -> (Sender<T, C>, Receiver<T, C>) {
+    assert!(
+        capacity > 0,
+        "a bounded channel needs a capacity of at least 1"
+    );
+    from_parts(BinaryHeap::from_vec_cmp(Vec::new(), cmp), Some(capacity))
+}
+
+/// Creates a channel that holds any number of pending messages, ordered by the comparator `cmp`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use proptest_binary_heap_example::{channel, MinComparator};
+/// let (tx, rx) = channel::unbounded_with_cmp(MinComparator);
+/// tx.try_send(2).unwrap();
+/// tx.try_send(1).unwrap();
+/// assert_eq!(rx.try_recv(), Ok(1));
+/// ```
+#[must_use]
+pub fn unbounded_with_cmp<T, C: Compare<T>>(cmp: C) -> (Sender<T, C>, Receiver<T, C>) {
+    from_parts(BinaryHeap::from_vec_cmp(Vec::new(), cmp), None)
+}
+
+fn from_parts<T, C>(
+    heap: BinaryHeap<T, C>,
+    capacity: Option<usize>,
+) -> (Sender<T, C>, Receiver<T, C>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            heap,
+            senders: 1,
+            receivers: 1,
+            recv_waiters: WaitList::new(),
+            send_waiters: WaitList::new(),
+        }),
+        capacity,
+    });
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+    (sender, Receiver { shared })
+}
+
+/// The state shared by all the halves of a channel.
+struct Shared<T, C> {
+    state: Mutex<State<T, C>>,
+    capacity: Option<usize>,
+}
+
+struct State<T, C> {
+    heap: BinaryHeap<T, C>,
+    senders: usize,
+    receivers: usize,
+    /// Receivers waiting for a message, or for the last sender to be dropped.
+    recv_waiters: WaitList,
+    /// Senders waiting for room in a bounded channel, or for the last receiver to be dropped.
+    send_waiters: WaitList,
+}
+
+impl<T, C> Shared<T, C> {
+    fn lock(&self) -> MutexGuard<'_, State<T, C>> {
+        // The heap is left in a consistent state even if a comparator panics, so poisoning can be
+        // ignored.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_full(&self, state: &State<T, C>) -> bool {
+        self.capacity
+            .is_some_and(|capacity| state.heap.len() >= capacity)
+    }
+}
+
+/// A queue of the wakers of futures that are waiting on the channel.
+///
+/// Each waiting future holds a key to its entry, so that it can update its waker when it is polled
+/// again, and remove it when it is dropped. Waking a future removes its entry: a future that is
+/// dropped after it was woken, but before it could make progress, passes the wakeup on to the next
+/// waiter so that it isn't lost.
+///
+/// The wakers are taken out of the list while the channel is locked, but only woken once the lock
+/// is released, with [`wake`]. An executor may poll the woken future right away, which would
+/// otherwise deadlock on the lock.
+struct WaitList {
+    waiters: VecDeque<(u64, Waker)>,
+    next_key: u64,
+}
+
+impl WaitList {
+    fn new() -> Self {
+        WaitList {
+            waiters: VecDeque::new(),
+            next_key: 0,
+        }
+    }
+
+    /// Registers `waker` to be woken, or updates the waker registered under `key`.
+    fn register(&mut self, key: &mut Option<u64>, waker: &Waker) {
+        if let Some(key) = *key {
+            if let Some((_, registered)) = self.waiters.iter_mut().find(|(k, _)| *k == key) {
+                registered.clone_from(waker);
+                return;
+            }
+        }
+        let new_key = self.next_key;
+        self.next_key += 1;
+        self.waiters.push_back((new_key, waker.clone()));
+        *key = Some(new_key);
+    }
+
+    /// Removes the entry for `key`, if there is one. Returns true if the future was woken since it
+    /// registered.
+    fn unregister(&mut self, key: &mut Option<u64>) -> bool {
+        let Some(key) = key.take() else {
+            return false;
+        };
+        match self.waiters.iter().position(|(k, _)| *k == key) {
+            Some(pos) => {
+                self.waiters.remove(pos);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Removes the first waiter, and returns its waker.
+    #[must_use = "the waker must be woken once the lock is released"]
+    fn take_one(&mut self) -> Option<Waker> {
+        self.waiters.pop_front().map(|(_, waker)| waker)
+    }
+
+    /// Removes every waiter, and returns their wakers.
+    #[must_use = "the wakers must be woken once the lock is released"]
+    fn take_all(&mut self) -> Vec<Waker> {
+        self.waiters.drain(..).map(|(_, waker)| waker).collect()
+    }
+}
+
+/// Wakes the wakers taken out of a [`WaitList`], after the lock has been released.
+fn wake(wakers: impl IntoIterator<Item = Waker>) {
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// The sending half of a priority channel, created by [`bounded`] or [`unbounded`].
+///
+/// Senders can be cloned to send from several tasks. The channel is closed for receiving once
+/// every sender has been dropped.
+pub struct Sender<T, C = MaxComparator> {
+    shared: Arc<Shared<T, C>>,
+}
+
+impl<T, C: Compare<T>> Sender<T, C> {
+    /// Sends a message, waiting for room if the channel is bounded and full.
+    ///
+    /// The returned future resolves to an error that hands the message back if every
+    /// [`Receiver`] has been dropped. If the future is dropped before it completes, the message
+    /// is dropped without being sent.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::channel;
+    /// # use std::future::Future;
+    /// # use std::pin::pin;
+    /// # use std::task::{Context, Poll, Waker};
+    /// # fn block_on<F: Future>(fut: F) -> F::Output {
+    /// #     let mut fut = pin!(fut);
+    /// #     let mut cx = Context::from_waker(Waker::noop());
+    /// #     loop {
+    /// #         if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    /// let (tx, rx) = channel::bounded(4);
+    /// block_on(async {
+    ///     tx.send(3).await.unwrap();
+    ///     assert_eq!(rx.recv().await, Some(3));
+    ///
+    ///     drop(rx);
+    ///     assert_eq!(tx.send(4).await.unwrap_err().into_inner(), 4);
+    /// });
+    /// ```
+    pub fn send(&self, item: T) -> Send<'_, T, C> {
+        Send {
+            sender: self,
+            item: Some(item),
+            key: None,
+        }
+    }
+
+    /// Sends a message if there is room for it, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TrySendError::Full`] if the channel is bounded and full, and
+    /// [`TrySendError::Closed`] if every [`Receiver`] has been dropped. Both hand the message back.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::channel::{self, TrySendError};
+    /// let (tx, rx) = channel::bounded(1);
+    /// assert_eq!(tx.try_send(1), Ok(()));
+    /// assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(rx);
+    /// assert_eq!(tx.try_send(3), Err(TrySendError::Closed(3)));
+    /// ```
+    pub fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.shared.lock();
+        if state.receivers == 0 {
+            return Err(TrySendError::Closed(item));
+        }
+        if self.shared.is_full(&state) {
+            return Err(TrySendError::Full(item));
+        }
+        state.heap.push(item);
+        let waker = state.recv_waiters.take_one();
+        drop(state);
+        wake(waker);
+        Ok(())
+    }
+}
+
+impl<T, C> Sender<T, C> {
+    /// Returns true if every [`Receiver`] has been dropped, so that sending fails.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().receivers == 0
+    }
+
+    /// Returns the maximum number of pending messages, or `None` if the channel is unbounded.
+    #[must_use]
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity
+    }
+
+    /// Returns the number of messages that have been sent, but not received yet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared.lock().heap.len()
+    }
+
+    /// Checks if there are no pending messages.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.shared.lock().heap.is_empty()
+    }
+}
+
+impl<T, C> Clone for Sender<T, C> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C> Drop for Sender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // Receivers waiting for a message now get `None` instead.
+            let wakers = state.recv_waiters.take_all();
+            drop(state);
+            wake(wakers);
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Sender<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.shared.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a priority channel, created by [`bounded`] or [`unbounded`].
+///
+/// Receivers can be cloned to receive from several tasks; each message goes to exactly one of
+/// them. The channel is closed for sending once every receiver has been dropped.
+pub struct Receiver<T, C = MaxComparator> {
+    shared: Arc<Shared<T, C>>,
+}
+
+impl<T, C: Compare<T>> Receiver<T, C> {
+    /// Receives the greatest pending message, waiting for one to be sent if there is none.
+    ///
+    /// The returned future resolves to `None` once every [`Sender`] has been dropped and there are
+    /// no pending messages left.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::channel;
+    /// # use std::future::Future;
+    /// # use std::pin::pin;
+    /// # use std::task::{Context, Poll, Waker};
+    /// # fn block_on<F: Future>(fut: F) -> F::Output {
+    /// #     let mut fut = pin!(fut);
+    /// #     let mut cx = Context::from_waker(Waker::noop());
+    /// #     loop {
+    /// #         if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    /// let (tx, rx) = channel::unbounded();
+    /// tx.try_send(1).unwrap();
+    /// tx.try_send(2).unwrap();
+    /// drop(tx);
+    ///
+    /// block_on(async {
+    ///     assert_eq!(rx.recv().await, Some(2));
+    ///     assert_eq!(rx.recv().await, Some(1));
+    ///     assert_eq!(rx.recv().await, None);
+    /// });
+    /// ```
+    pub fn recv(&self) -> Recv<'_, T, C> {
+        Recv {
+            receiver: self,
+            key: None,
+        }
+    }
+
+    /// Receives the greatest pending message, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryRecvError::Empty`] if there are no pending messages, and
+    /// [`TryRecvError::Closed`] if additionally every [`Sender`] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::channel::{self, TryRecvError};
+    /// let (tx, rx) = channel::unbounded();
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// tx.try_send(1).unwrap();
+    /// drop(tx);
+    /// assert_eq!(rx.try_recv(), Ok(1));
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock();
+        match state.heap.pop() {
+            Some(item) => {
+                let waker = state.send_waiters.take_one();
+                drop(state);
+                wake(waker);
+                Ok(item)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T, C> Receiver<T, C> {
+    /// Returns true if every [`Sender`] has been dropped. There may still be pending messages.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().senders == 0
+    }
+
+    /// Returns the maximum number of pending messages, or `None` if the channel is unbounded.
+    #[must_use]
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity
+    }
+
+    /// Returns the number of messages that have been sent, but not received yet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared.lock().heap.len()
+    }
+
+    /// Checks if there are no pending messages.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.shared.lock().heap.is_empty()
+    }
+}
+
+impl<T, C> Clone for Receiver<T, C> {
+    fn clone(&self) -> Self {
+        self.shared.lock().receivers += 1;
+        Receiver {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C> Drop for Receiver<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            // Senders waiting for room now fail instead.
+            let wakers = state.send_waiters.take_all();
+            drop(state);
+            wake(wakers);
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Receiver<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.shared.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`Sender::send`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Send<'a, T, C = MaxComparator> {
+    sender: &'a Sender<T, C>,
+    /// The message, until it has been sent.
+    item: Option<T>,
+    key: Option<u64>,
+}
+
+// The message is never pinned: it is only ever moved into the heap.
+impl<T, C> Unpin for Send<'_, T, C> {}
+
+impl<T, C: Compare<T>> Future for Send<'_, T, C> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let shared = &this.sender.shared;
+        let mut state = shared.lock();
+        if state.receivers == 0 || !shared.is_full(&state) {
+            state.send_waiters.unregister(&mut this.key);
+            let item = this.item.take().expect("`Send` polled after completion");
+            if state.receivers == 0 {
+                return Poll::Ready(Err(SendError(item)));
+            }
+            state.heap.push(item);
+            let waker = state.recv_waiters.take_one();
+            drop(state);
+            wake(waker);
+            return Poll::Ready(Ok(()));
+        }
+        state.send_waiters.register(&mut this.key, cx.waker());
+        Poll::Pending
+    }
+}
+
+impl<T, C> Drop for Send<'_, T, C> {
+    fn drop(&mut self) {
+        if self.key.is_some() {
+            let mut state = self.sender.shared.lock();
+            if state.send_waiters.unregister(&mut self.key) {
+                // This future was woken because there is room, but won't use it.
+                let waker = state.send_waiters.take_one();
+                drop(state);
+                wake(waker);
+            }
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Send<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Send")
+            .field("done", &self.item.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`Receiver::recv`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Recv<'a, T, C = MaxComparator> {
+    receiver: &'a Receiver<T, C>,
+    key: Option<u64>,
+}
+
+impl<T, C: Compare<T>> Future for Recv<'_, T, C> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut state = this.receiver.shared.lock();
+        if let Some(item) = state.heap.pop() {
+            state.recv_waiters.unregister(&mut this.key);
+            let waker = state.send_waiters.take_one();
+            drop(state);
+            wake(waker);
+            return Poll::Ready(Some(item));
+        }
+        if state.senders == 0 {
+            state.recv_waiters.unregister(&mut this.key);
+            return Poll::Ready(None);
+        }
+        state.recv_waiters.register(&mut this.key, cx.waker());
+        Poll::Pending
+    }
+}
+
+impl<T, C> Drop for Recv<'_, T, C> {
+    fn drop(&mut self) {
+        if self.key.is_some() {
+            let mut state = self.receiver.shared.lock();
+            if state.recv_waiters.unregister(&mut self.key) {
+                // This future was woken because a message arrived, but won't receive it.
+                let waker = state.recv_waiters.take_one();
+                drop(state);
+                wake(waker);
+            }
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Recv<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recv").finish_non_exhaustive()
+    }
+}
+
+/// An error returned by [`Sender::send`] when every [`Receiver`] has been dropped.
+///
+/// It contains the message that couldn't be sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> SendError<T> {
+    /// Returns the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a closed channel")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+/// An error returned by [`Sender::try_send`].
+///
+/// Both variants contain the message that couldn't be sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is bounded and full.
+    Full(T),
+    /// Every [`Receiver`] has been dropped.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(item) | TrySendError::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("sending on a full channel"),
+            TrySendError::Closed(_) => f.write_str("sending on a closed channel"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for TrySendError<T> {}
+
+/// An error returned by [`Receiver::try_recv`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// There are no pending messages, but more may be sent.
+    Empty,
+    /// There are no pending messages, and every [`Sender`] has been dropped.
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("receiving on an empty channel"),
+            TryRecvError::Closed => f.write_str("receiving on an empty and closed channel"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
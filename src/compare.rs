@@ -0,0 +1,99 @@
+//! Comparators that determine the order of elements in a heap.
+//!
+//! Every heap in this crate is a max-heap with respect to its comparator: the element that compares
+//! greatest is the one returned by `peek` and `pop`. The default comparator, [`MaxComparator`],
+//! uses the element's [`Ord`] implementation, so the default heap is a max-heap in the usual sense.
+//! [`MinComparator`] flips this around, and [`KeyComparator`] and [`FnComparator`] allow ordering
+//! by a key or by an arbitrary closure, without having to wrap elements in
+//! [`Reverse`](core::cmp::Reverse) or a newtype.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::BinaryHeap;
+//!
+//! let mut heap = BinaryHeap::new_by_key(|s: &&str| s.len());
+//! heap.extend(["a", "abc", "ab"]);
+//!
+//! assert_eq!(heap.pop(), Some("abc"));
+//! assert_eq!(heap.pop(), Some("ab"));
+//! assert_eq!(heap.pop(), Some("a"));
+//! ```
+
+use core::cmp::Ordering;
+
+/// A comparison function over values of type `T`.
+///
+/// It is a logic error for the comparator to not implement a [total order], or for the result of
+/// comparing two elements to change while they are in a heap. The behavior resulting from such a
+/// logic error is not specified, but will not be undefined behavior.
+///
+/// [total order]: https://en.wikipedia.org/wiki/Total_order
+pub trait Compare<T: ?Sized> {
+    /// Compares `a` and `b`. The heap will put elements that compare greater closer to the top.
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T: ?Sized, C: Compare<T> + ?Sized> Compare<T> for &C {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
+    }
+}
+
+/// A comparator that uses the natural order of `T`, producing a max-heap.
+///
+/// This is the default comparator for [`BinaryHeap`](crate::BinaryHeap).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaxComparator;
+
+impl<T: Ord + ?Sized> Compare<T> for MaxComparator {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// A comparator that reverses the natural order of `T`, producing a min-heap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MinComparator;
+
+impl<T: Ord + ?Sized> Compare<T> for MinComparator {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+/// A comparator that calls a closure to compare two elements.
+///
+/// The closure is expected to return the ordering of its first argument relative to its second,
+/// just like [`Ord::cmp`]. The heap puts the greatest element on top.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FnComparator<F>(pub F);
+
+impl<T: ?Sized, F> Compare<T> for FnComparator<F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+/// A comparator that orders elements by a key extracted with a closure.
+///
+/// The heap puts the element with the greatest key on top.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyComparator<F>(pub F);
+
+impl<K: Ord, T: ?Sized, F> Compare<T> for KeyComparator<F>
+where
+    F: Fn(&T) -> K,
+{
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
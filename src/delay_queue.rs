@@ -0,0 +1,543 @@
+//! A queue of items that each become available once their deadline has passed.
+//!
+//! [`DelayQueue`] is the building block for timeouts and retries: every item is inserted with a
+//! deadline, and comes back out of [`poll_expired`](DelayQueue::poll_expired) or
+//! [`wait_next`](DelayQueue::wait_next) once that deadline has passed, earliest deadline first.
+//! Inserting an item returns a [`Key`] through which its deadline can be pushed back with
+//! [`reset`](DelayQueue::reset), or the item removed before it expires with
+//! [`remove`](DelayQueue::remove).
+//!
+//! The queue is built on an [`IndexedHeap`], so resetting or removing an item happens in place in
+//! *O*(log(*n*)) time, instead of leaving a tombstone behind in the heap.
+//!
+//! The current time comes from a [`Clock`]. By default this is [`SystemClock`], which reads
+//! [`Instant::now`] and really sleeps. [`ManualClock`] only moves forward when it is told to, which
+//! makes code that uses the queue deterministic to test.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::delay_queue::{Clock, DelayQueue, ManualClock};
+//! use std::time::Duration;
+//!
+//! let clock = ManualClock::new();
+//! let mut queue = DelayQueue::with_clock(&clock);
+//! let retry = queue.insert("retry request", Duration::from_secs(5));
+//! queue.insert("time out request", Duration::from_secs(30));
+//!
+//! clock.advance(Duration::from_secs(3));
+//! assert_eq!(queue.poll_expired(clock.now()), None);
+//!
+//! // The request failed again: back off further.
+//! queue.reset(retry, clock.now() + Duration::from_secs(10));
+//! clock.advance(Duration::from_secs(10));
+//! assert_eq!(queue.poll_expired(clock.now()), Some("retry request"));
+//!
+//! // `wait_next` sleeps on the clock until the next deadline.
+//! assert_eq!(queue.wait_next(), Some("time out request"));
+//! assert_eq!(clock.elapsed(), Duration::from_secs(30));
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::atomic::{self, AtomicU64};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::compare::Compare;
+use crate::indexed_heap::{Handle, IndexedHeap};
+
+/// How far ahead [`DelayQueue::insert`] sets the deadline of an item whose timeout doesn't fit in
+/// an [`Instant`]. About 30 years is as good as never.
+const FAR_FUTURE: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 30);
+
+/// A source of the current time for a [`DelayQueue`].
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Blocks the current thread until `deadline` has passed.
+    fn sleep_until(&self, deadline: Instant);
+}
+
+impl<K: Clock + ?Sized> Clock for &K {
+    #[inline]
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    #[inline]
+    fn sleep_until(&self, deadline: Instant) {
+        (**self).sleep_until(deadline);
+    }
+}
+
+/// The system's monotonic clock, which [`DelayQueue`] uses by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        let now = Instant::now();
+        if deadline > now {
+            thread::sleep(deadline - now);
+        }
+    }
+}
+
+/// A clock that only moves forward when it is advanced, or when something sleeps on it.
+///
+/// Sleeping on a `ManualClock` returns immediately, after advancing the clock to the deadline.
+/// This lets tests run code that waits on a [`DelayQueue`] without actually waiting. To share
+/// the clock with a queue, pass the queue a reference to it.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use proptest_binary_heap_example::delay_queue::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_millis(10));
+/// assert_eq!(clock.now() - start, Duration::from_millis(10));
+///
+/// clock.sleep_until(start + Duration::from_millis(25));
+/// assert_eq!(clock.elapsed(), Duration::from_millis(25));
+/// ```
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    /// Nanoseconds since `start`.
+    elapsed: AtomicU64,
+}
+
+impl ManualClock {
+    /// Creates a clock that starts at the current time, and then stands still.
+    #[must_use]
+    pub fn new() -> Self {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: AtomicU64::new(0),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).expect("duration is too long");
+        self.elapsed.fetch_add(nanos, atomic::Ordering::SeqCst);
+    }
+
+    /// Returns how far the clock has moved forward since it was created.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed.load(atomic::Ordering::SeqCst))
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        let nanos = u64::try_from(deadline.saturating_duration_since(self.start).as_nanos())
+            .expect("deadline is too far in the future");
+        self.elapsed.fetch_max(nanos, atomic::Ordering::SeqCst);
+    }
+}
+
+/// A stable reference to an item in a [`DelayQueue`].
+///
+/// A key stays valid until its item expires or is removed. After that, all methods that take the
+/// key treat it as absent.
+///
+/// It is a logic error to use a key with a queue other than the one that created it. The behavior
+/// resulting from such a logic error is not specified, but will not be undefined behavior.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key(Handle);
+
+/// A queue of items ordered by deadline, that each become available once their deadline has
+/// passed.
+///
+/// Items expire earliest deadline first. Items with the same deadline expire in the order they
+/// were inserted, or had their deadline reset.
+///
+/// # Time complexity
+///
+/// | [insert]      | [poll_expired] | [next_deadline] | [reset]/[remove] |
+/// |---------------|----------------|-----------------|------------------|
+/// | *O*(log(*n*)) | *O*(log(*n*))  | *O*(1)          | *O*(log(*n*))    |
+///
+/// [insert]: DelayQueue::insert
+/// [poll_expired]: DelayQueue::poll_expired
+/// [next_deadline]: DelayQueue::next_deadline
+/// [reset]: DelayQueue::reset
+/// [remove]: DelayQueue::remove
+pub struct DelayQueue<T, K = SystemClock> {
+    heap: IndexedHeap<Expiration<T>, DeadlineComparator>,
+    clock: K,
+    /// Breaks ties between equal deadlines, so that they expire in insertion order.
+    next_seq: u64,
+}
+
+struct Expiration<T> {
+    deadline: Instant,
+    seq: u64,
+    item: T,
+}
+
+/// Orders expirations so that the earliest deadline, and then the lowest sequence number, is the
+/// greatest.
+struct DeadlineComparator;
+
+impl<T> Compare<Expiration<T>> for DeadlineComparator {
+    #[inline]
+    fn compare(&self, a: &Expiration<T>, b: &Expiration<T>) -> Ordering {
+        (b.deadline, b.seq).cmp(&(a.deadline, a.seq))
+    }
+}
+
+impl<T> DelayQueue<T> {
+    /// Creates an empty `DelayQueue` that uses the system clock.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::DelayQueue;
+    /// use std::time::Duration;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// queue.insert("now", Duration::ZERO);
+    /// assert_eq!(queue.wait_next(), Some("now"));
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        DelayQueue::with_clock(SystemClock)
+    }
+}
+
+impl<T, K: Clock> DelayQueue<T, K> {
+    /// Creates an empty `DelayQueue` that reads the time from `clock`.
+    #[must_use]
+    pub fn with_clock(clock: K) -> Self {
+        DelayQueue {
+            heap: IndexedHeap::with_cmp(DeadlineComparator),
+            clock,
+            next_seq: 0,
+        }
+    }
+
+    /// Inserts an item that expires at `deadline`, and returns a key that refers to it.
+    ///
+    /// A deadline in the past is allowed, and makes the item expire right away.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::DelayQueue;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// let deadline = Instant::now() + Duration::from_secs(60);
+    /// let key = queue.insert_at("later", deadline);
+    /// assert_eq!(queue.deadline(key), Some(deadline));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `insert_at` on a queue containing *n* items is *O*(log(*n*)).
+    pub fn insert_at(&mut self, item: T, deadline: Instant) -> Key {
+        let seq = self.bump_seq();
+        Key(self.heap.push(Expiration {
+            deadline,
+            seq,
+            item,
+        }))
+    }
+
+    /// Inserts an item that expires `timeout` from now, and returns a key that refers to it.
+    ///
+    /// A timeout too long to be represented as an [`Instant`], such as [`Duration::MAX`], is
+    /// clamped to about 30 years, so that it can be used to mean "never".
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::{Clock, DelayQueue, ManualClock};
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(&clock);
+    /// let key = queue.insert("soon", Duration::from_secs(1));
+    /// assert_eq!(queue.deadline(key), Some(clock.now() + Duration::from_secs(1)));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `insert` on a queue containing *n* items is *O*(log(*n*)).
+    pub fn insert(&mut self, item: T, timeout: Duration) -> Key {
+        let now = self.clock.now();
+        let deadline = now.checked_add(timeout).unwrap_or_else(|| now + FAR_FUTURE);
+        self.insert_at(item, deadline)
+    }
+
+    /// Changes the deadline of the item that `key` refers to. Returns false if the item has
+    /// already expired or been removed.
+    ///
+    /// The item expires after any other items with the same deadline that are already in the
+    /// queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::{Clock, DelayQueue, ManualClock};
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(&clock);
+    /// let key = queue.insert("retry", Duration::from_secs(1));
+    ///
+    /// assert!(queue.reset(key, clock.now() + Duration::from_secs(5)));
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(queue.poll_expired(clock.now()), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `reset` on a queue containing *n* items is *O*(log(*n*)).
+    pub fn reset(&mut self, key: Key, deadline: Instant) -> bool {
+        let seq = self.next_seq;
+        let reset = self.heap.update(key.0, |expiration| {
+            expiration.deadline = deadline;
+            expiration.seq = seq;
+        });
+        if reset {
+            self.bump_seq();
+        }
+        reset
+    }
+
+    /// Removes the item that `key` refers to before it expires, and returns it. Returns `None`
+    /// if the item has already expired or been removed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::DelayQueue;
+    /// use std::time::Duration;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// let key = queue.insert("timeout", Duration::from_secs(30));
+    ///
+    /// // The request completed in time.
+    /// assert_eq!(queue.remove(key), Some("timeout"));
+    /// assert_eq!(queue.remove(key), None);
+    /// assert!(queue.is_empty());
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `remove` on a queue containing *n* items is *O*(log(*n*)).
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        self.heap.remove(key.0).map(|expiration| expiration.item)
+    }
+
+    /// Removes the item with the earliest deadline and returns it, if that deadline is at or
+    /// before `now`. Otherwise, returns `None`.
+    ///
+    /// Call this in a loop to collect every item that has expired by `now`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::{Clock, DelayQueue, ManualClock};
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(&clock);
+    /// queue.insert(2, Duration::from_secs(2));
+    /// queue.insert(1, Duration::from_secs(1));
+    /// queue.insert(3, Duration::from_secs(3));
+    ///
+    /// clock.advance(Duration::from_secs(2));
+    /// let mut expired = Vec::new();
+    /// while let Some(item) = queue.poll_expired(clock.now()) {
+    ///     expired.push(item);
+    /// }
+    /// assert_eq!(expired, [1, 2]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `poll_expired` on a queue containing *n* items is *O*(log(*n*)).
+    pub fn poll_expired(&mut self, now: Instant) -> Option<T> {
+        if self.next_deadline()? > now {
+            return None;
+        }
+        self.heap.pop().map(|expiration| expiration.item)
+    }
+
+    /// Removes the item with the earliest deadline and returns it, sleeping on the clock until
+    /// the deadline has passed. Returns `None` right away if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::{DelayQueue, ManualClock};
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(&clock);
+    /// queue.insert("a", Duration::from_secs(1));
+    ///
+    /// assert_eq!(queue.wait_next(), Some("a"));
+    /// assert_eq!(clock.elapsed(), Duration::from_secs(1));
+    /// assert_eq!(queue.wait_next(), None);
+    /// ```
+    pub fn wait_next(&mut self) -> Option<T> {
+        loop {
+            let deadline = self.next_deadline()?;
+            if let Some(item) = self.poll_expired(self.clock.now()) {
+                return Some(item);
+            }
+            self.clock.sleep_until(deadline);
+        }
+    }
+
+    fn bump_seq(&mut self) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        seq
+    }
+}
+
+impl<T, K> DelayQueue<T, K> {
+    /// Returns the earliest deadline of any item in the queue, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::delay_queue::DelayQueue;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// assert_eq!(queue.next_deadline(), None);
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// queue.insert_at("a", deadline);
+    /// queue.insert_at("b", deadline + Duration::from_secs(1));
+    /// assert_eq!(queue.next_deadline(), Some(deadline));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.heap.peek().map(|expiration| expiration.deadline)
+    }
+
+    /// Returns a reference to the item that `key` refers to, or `None` if it has already expired
+    /// or been removed.
+    #[must_use]
+    pub fn get(&self, key: Key) -> Option<&T> {
+        self.heap.get(key.0).map(|expiration| &expiration.item)
+    }
+
+    /// Returns the deadline of the item that `key` refers to, or `None` if it has already expired
+    /// or been removed.
+    #[must_use]
+    pub fn deadline(&self, key: Key) -> Option<Instant> {
+        self.heap.get(key.0).map(|expiration| expiration.deadline)
+    }
+
+    /// Returns true if the item that `key` refers to is still in the queue.
+    #[must_use]
+    pub fn contains(&self, key: Key) -> bool {
+        self.heap.contains(key.0)
+    }
+
+    /// Returns a reference to the queue's clock.
+    #[must_use]
+    pub fn clock(&self) -> &K {
+        &self.clock
+    }
+
+    /// Returns the number of items in the queue, expired or not.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if the queue is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Drops all items from the queue. All keys become invalid.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+}
+
+impl<T> Default for DelayQueue<T> {
+    /// Creates an empty `DelayQueue<T>` that uses the system clock.
+    fn default() -> Self {
+        DelayQueue::new()
+    }
+}
+
+impl<T: fmt::Debug, K: fmt::Debug> fmt::Debug for DelayQueue<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelayQueue")
+            .field("items", &DebugItems(&self.heap))
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+/// Formats the items of a queue as a map from deadline to item, in arbitrary order.
+struct DebugItems<'a, T>(&'a IndexedHeap<Expiration<T>, DeadlineComparator>);
+
+impl<T: fmt::Debug> fmt::Debug for DebugItems<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|(_, expiration)| (expiration.deadline, &expiration.item)),
+            )
+            .finish()
+    }
+}
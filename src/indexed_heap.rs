@@ -0,0 +1,648 @@
+//! An addressable priority queue, where every element can be looked up, updated or removed through
+//! the [`Handle`] returned when it was pushed.
+//!
+//! [`IndexedHeap`] uses the same sifting routines as [`BinaryHeap`](crate::BinaryHeap), but
+//! additionally records the current position of every element in the heap. Whenever the sifting
+//! routines move an element, its recorded position is updated. This makes it possible to find an
+//! element from its handle in *O*(1) time, and to restore the heap property after changing or
+//! removing an arbitrary element in *O*(log(*n*)) time.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::IndexedHeap;
+//!
+//! let mut heap = IndexedHeap::new();
+//! let a = heap.push(1);
+//! let b = heap.push(5);
+//! let c = heap.push(3);
+//!
+//! // Make `a` the greatest element.
+//! heap.update(a, |x| *x = 10);
+//! assert_eq!(heap.peek(), Some(&10));
+//!
+//! // Remove `b` from the middle of the heap.
+//! assert_eq!(heap.remove(b), Some(5));
+//! assert_eq!(heap.get(b), None);
+//!
+//! assert_eq!(heap.pop(), Some(10));
+//! assert_eq!(heap.pop(), Some(3));
+//! assert_eq!(heap.get(c), None);
+//! assert!(heap.is_empty());
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::binary_heap::{sift_down_range, sift_down_to_bottom, sift_up, Track};
+use crate::compare::{Compare, MaxComparator, MinComparator};
+use crate::queue::PriorityQueue;
+
+/// A stable reference to an element in an [`IndexedHeap`].
+///
+/// A handle stays valid until the element it refers to is popped or removed. After that, all
+/// methods that take the handle treat it as absent, even if the heap has since reused its storage
+/// for a different element.
+///
+/// It is a logic error to use a handle with a heap other than the one that created it. The
+/// behavior resulting from such a logic error is not specified, but will not be undefined behavior.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u64,
+}
+
+/// An addressable priority queue implemented with a binary heap.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`. Unlike [`BinaryHeap`](crate::BinaryHeap), [`push`](IndexedHeap::push) returns a
+/// [`Handle`] through which the element can later be accessed, changed or removed.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the heap, except through [`update`](Self::update) or
+/// [`change_priority`](Self::change_priority). The behavior resulting from such a logic error is
+/// not specified, but will not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [push]        | [pop]         | [peek]/[get] | [update]/[remove] |
+/// |---------------|---------------|--------------|-------------------|
+/// | *O*(log(*n*)) | *O*(log(*n*)) | *O*(1)       | *O*(log(*n*))     |
+///
+/// [push]: IndexedHeap::push
+/// [pop]: IndexedHeap::pop
+/// [peek]: IndexedHeap::peek
+/// [get]: IndexedHeap::get
+/// [update]: IndexedHeap::update
+/// [remove]: IndexedHeap::remove
+pub struct IndexedHeap<T, C = MaxComparator> {
+    data: Vec<Entry<T>>,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+    cmp: EntryComparator<C>,
+}
+
+/// An element in the heap, along with the index of the slot that records its position.
+struct Entry<T> {
+    slot: usize,
+    item: T,
+}
+
+/// Records where the element for a handle currently lives in `data`.
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    /// The index into `data`, or `None` if the slot is free.
+    pos: Option<usize>,
+    /// Incremented every time the slot is freed, so that stale handles can be detected.
+    generation: u64,
+}
+
+/// Compares entries by their items.
+struct EntryComparator<C>(C);
+
+impl<T, C: Compare<T>> Compare<Entry<T>> for EntryComparator<C> {
+    #[inline]
+    fn compare(&self, a: &Entry<T>, b: &Entry<T>) -> Ordering {
+        self.0.compare(&a.item, &b.item)
+    }
+}
+
+/// Updates slots as the sifting routines move entries around.
+struct Positions<'a>(&'a mut [Slot]);
+
+impl<T> Track<Entry<T>> for Positions<'_> {
+    #[inline]
+    fn track(&mut self, elt: &Entry<T>, pos: usize) {
+        self.0[elt.slot].pos = Some(pos);
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for IndexedHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.data.iter().map(|entry| &entry.item))
+            .finish()
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for IndexedHeap<T, C> {
+    /// Creates an empty `IndexedHeap<T, C>`.
+    #[inline]
+    fn default() -> Self {
+        IndexedHeap::with_cmp(C::default())
+    }
+}
+
+impl<T: Ord> IndexedHeap<T> {
+    /// Creates an empty `IndexedHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        IndexedHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T: Ord> IndexedHeap<T, MinComparator> {
+    /// Creates an empty `IndexedHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new_min();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        IndexedHeap::with_cmp(MinComparator)
+    }
+}
+
+impl<T, C: Compare<T>> IndexedHeap<T, C> {
+    /// Creates an empty `IndexedHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{IndexedHeap, KeyComparator};
+    /// let mut heap = IndexedHeap::with_cmp(KeyComparator(|x: &i32| x.abs()));
+    /// heap.push(3);
+    /// heap.push(-7);
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        IndexedHeap {
+            data: Vec::new(),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            cmp: EntryComparator(cmp),
+        }
+    }
+
+    /// Pushes an item onto the heap, returning a handle that refers to it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(3);
+    /// heap.push(5);
+    ///
+    /// assert_eq!(heap.get(handle), Some(&3));
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn push(&mut self, item: T) -> Handle {
+        let pos = self.data.len();
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot].pos = Some(pos);
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    pos: Some(pos),
+                    generation: 0,
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.data.push(Entry { slot, item });
+        // SAFETY: Since we pushed a new item it means that
+        //  pos = self.len() - 1 < self.len()
+        unsafe {
+            sift_up::<2, _, _, _>(
+                &mut self.data,
+                &self.cmp,
+                &mut Positions(&mut self.slots),
+                0,
+                pos,
+            )
+        };
+
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// The handle that referred to the item becomes invalid.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(3);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.get(handle), None);
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    /// Returns a reference to the item that `handle` refers to, or `None` if it is no longer in
+    /// the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(3);
+    /// assert_eq!(heap.get(handle), Some(&3));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|pos| &self.data[pos].item)
+    }
+
+    /// Returns true if the item that `handle` refers to is still in the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(3);
+    /// assert!(heap.contains(handle));
+    /// heap.pop();
+    /// assert!(!heap.contains(handle));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    /// Calls `f` on the item that `handle` refers to, then moves the item up or down the heap to
+    /// restore the heap property. Returns `false` if the item is no longer in the heap, in which
+    /// case `f` is not called.
+    ///
+    /// If `f` panics, the heap may be left in an inconsistent state.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(1);
+    /// heap.push(5);
+    ///
+    /// assert!(heap.update(handle, |x| *x = 7));
+    /// assert_eq!(heap.peek(), Some(&7));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `update` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn update<F>(&mut self, handle: Handle, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        match self.position(handle) {
+            Some(pos) => {
+                f(&mut self.data[pos].item);
+                self.restore(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the item that `handle` refers to with `item`, moving it up or down the heap as
+    /// necessary, and returns the old item.
+    ///
+    /// # Errors
+    ///
+    /// If the handle no longer refers to an item in the heap, `item` is returned as an error.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new_min();
+    /// heap.push(3);
+    /// let handle = heap.push(5);
+    ///
+    /// // Decrease the key of `handle`.
+    /// assert_eq!(heap.change_priority(handle, 1), Ok(5));
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `change_priority` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    pub fn change_priority(&mut self, handle: Handle, item: T) -> Result<T, T> {
+        match self.position(handle) {
+            Some(pos) => {
+                let old = core::mem::replace(&mut self.data[pos].item, item);
+                self.restore(pos);
+                Ok(old)
+            }
+            None => Err(item),
+        }
+    }
+
+    /// Removes the item that `handle` refers to from the heap and returns it, or `None` if it is no
+    /// longer in the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// heap.push(1);
+    /// let handle = heap.push(3);
+    /// heap.push(5);
+    ///
+    /// assert_eq!(heap.remove(handle), Some(3));
+    /// assert_eq!(heap.remove(handle), None);
+    /// assert_eq!(heap.pop(), Some(5));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `remove` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        self.position(handle).map(|pos| self.remove_at(pos))
+    }
+
+    /// Consumes the `IndexedHeap` and returns a vector in sorted (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// heap.push(2);
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop() {
+            vec.push(item);
+        }
+        vec.reverse();
+        vec
+    }
+
+    /// Removes the entry at `pos` and restores the heap property.
+    ///
+    /// `pos` must be less than `self.len()`.
+    fn remove_at(&mut self, pos: usize) -> T {
+        let entry = self.data.swap_remove(pos);
+        let slot = &mut self.slots[entry.slot];
+        slot.pos = None;
+        slot.generation += 1;
+        self.free_slots.push(entry.slot);
+
+        if pos < self.data.len() {
+            // The last entry was moved into `pos`, so update its slot before sifting.
+            self.slots[self.data[pos].slot].pos = Some(pos);
+            if pos == 0 {
+                // SAFETY: pos == 0 < self.len().
+                unsafe {
+                    sift_down_to_bottom::<2, _, _, _>(
+                        &mut self.data,
+                        &self.cmp,
+                        &mut Positions(&mut self.slots),
+                        0,
+                    )
+                };
+            } else {
+                self.restore(pos);
+            }
+        }
+        entry.item
+    }
+
+    /// Moves the entry at `pos` up or down the heap after it has been changed.
+    ///
+    /// `pos` must be less than `self.len()`.
+    fn restore(&mut self, pos: usize) {
+        assert!(pos < self.data.len());
+        let mut positions = Positions(&mut self.slots);
+        // SAFETY: pos < self.len() was checked above.
+        let new_pos =
+            unsafe { sift_up::<2, _, _, _>(&mut self.data, &self.cmp, &mut positions, 0, pos) };
+        if new_pos == pos {
+            let end = self.data.len();
+            // SAFETY: pos < end == self.len().
+            unsafe {
+                sift_down_range::<2, _, _, _>(&mut self.data, &self.cmp, &mut positions, pos, end)
+            };
+        }
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot)?;
+        if slot.generation == handle.generation {
+            slot.pos
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, C> IndexedHeap<T, C> {
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first().map(|entry| &entry.item)
+    }
+
+    /// Returns the length of the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// heap.push(1);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// assert!(heap.is_empty());
+    ///
+    /// heap.push(3);
+    /// assert!(!heap.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator visiting all handles and items in the heap, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(1);
+    ///
+    /// assert_eq!(heap.iter().collect::<Vec<_>>(), [(handle, &1)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> + '_ {
+        self.data.iter().map(move |entry| {
+            let handle = Handle {
+                slot: entry.slot,
+                generation: self.slots[entry.slot].generation,
+            };
+            (handle, &entry.item)
+        })
+    }
+
+    /// Drops all items from the heap. All handles become invalid.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::IndexedHeap;
+    /// let mut heap = IndexedHeap::new();
+    /// let handle = heap.push(1);
+    ///
+    /// heap.clear();
+    ///
+    /// assert!(heap.is_empty());
+    /// assert_eq!(heap.get(handle), None);
+    /// ```
+    pub fn clear(&mut self) {
+        for entry in &self.data {
+            let slot = &mut self.slots[entry.slot];
+            slot.pos = None;
+            slot.generation += 1;
+            self.free_slots.push(entry.slot);
+        }
+        self.data.clear();
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for IndexedHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for IndexedHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
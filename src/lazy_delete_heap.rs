@@ -0,0 +1,519 @@
+//! A binary heap that removes arbitrary items lazily, by recording them as tombstones.
+//!
+//! Removing an item from the middle of a [`BinaryHeap`] means finding it first, which takes
+//! *O*(*n*) time, as does [`retain`](crate::BinaryHeap::retain). When removals are rare, it is
+//! cheaper to write them down and deal with them later. [`LazyDeleteHeap::remove`] adds the item
+//! to a multiset of tombstones in *O*(1) time, and tombstoned items are discarded once they reach
+//! the top of the heap, where they would otherwise be peeked at or popped.
+//!
+//! Tombstoned items still take up room in the heap until then. To keep that from growing without
+//! bound, the heap is compacted, discarding every tombstoned item in one *O*(*n*) pass, once the
+//! tombstones make up more than a [configurable
+//! fraction](LazyDeleteHeap::set_compaction_threshold) of the stored items.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::LazyDeleteHeap;
+//!
+//! // Orders by price, best first.
+//! let mut orders = LazyDeleteHeap::new();
+//! orders.push((105, "order 1"));
+//! orders.push((110, "order 2"));
+//! orders.push((100, "order 3"));
+//!
+//! // The best order is cancelled.
+//! orders.remove(&(110, "order 2"));
+//! assert_eq!(orders.len(), 2);
+//! assert_eq!(orders.peek(), Some(&(105, "order 1")));
+//!
+//! assert_eq!(orders.into_sorted_vec(), [(100, "order 3"), (105, "order 1")]);
+//! ```
+
+use core::fmt;
+use core::hash::Hash;
+
+use alloc::vec::Vec;
+use std::collections::HashMap;
+
+use crate::binary_heap::BinaryHeap;
+use crate::compare::{Compare, MaxComparator, MinComparator};
+use crate::queue::PriorityQueue;
+
+/// The default for [`LazyDeleteHeap::compaction_threshold`].
+const DEFAULT_COMPACTION_THRESHOLD: f64 = 0.5;
+
+/// A binary heap that supports removing items by value, by skipping them once they reach the top.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`. Removed items are identified by their [`Hash`] and [`Eq`] implementations: removing an
+/// item removes one item that is equal to it.
+///
+/// It is a logic error to [`remove`](Self::remove) an item that isn't in the heap, since the
+/// tombstone might remove an equal item that is pushed later instead. It is also a logic error for
+/// an item to be modified in such a way that its ordering, hash or equality relative to any other
+/// item changes while it is in the heap. The behavior resulting from such logic errors is not
+/// specified, but will not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [push]        | [pop]           | [peek] | [remove] | [compact] |
+/// |---------------|-----------------|--------|----------|-----------|
+/// | *O*(log(*n*)) | *O*(log(*n*))\* | *O*(1) | *O*(1)\* | *O*(*n*)  |
+///
+/// The starred operations also discard tombstoned items that have reached the top of the heap,
+/// at *O*(log(*n*)) each, and may compact the heap. Every tombstone is discarded at most once, so
+/// this is amortized over the removals.
+///
+/// [push]: LazyDeleteHeap::push
+/// [pop]: LazyDeleteHeap::pop
+/// [peek]: LazyDeleteHeap::peek
+/// [remove]: LazyDeleteHeap::remove
+/// [compact]: LazyDeleteHeap::compact
+pub struct LazyDeleteHeap<T, C = MaxComparator> {
+    /// Every item that is stored, live or tombstoned. The top item is always live.
+    heap: BinaryHeap<T, C>,
+    /// How many items equal to each key are tombstoned.
+    tombstones: HashMap<T, usize>,
+    /// The sum of the counts in `tombstones`.
+    tombstone_count: usize,
+    compaction_threshold: f64,
+}
+
+impl<T: Ord + Hash> LazyDeleteHeap<T> {
+    /// Creates an empty `LazyDeleteHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        LazyDeleteHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T: Ord + Hash> LazyDeleteHeap<T, MinComparator> {
+    /// Creates an empty `LazyDeleteHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new_min();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        LazyDeleteHeap::with_cmp(MinComparator)
+    }
+}
+
+impl<T: Hash + Eq, C: Compare<T>> LazyDeleteHeap<T, C> {
+    /// Creates an empty `LazyDeleteHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, LazyDeleteHeap};
+    /// let mut heap = LazyDeleteHeap::with_cmp(KeyComparator(|x: &i32| x.abs()));
+    /// heap.push(3);
+    /// heap.push(-7);
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        LazyDeleteHeap {
+            heap: BinaryHeap::from_vec_cmp(Vec::new(), cmp),
+            tombstones: HashMap::new(),
+            tombstone_count: 0,
+            compaction_threshold: DEFAULT_COMPACTION_THRESHOLD,
+        }
+    }
+
+    /// Pushes an item onto the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push` on a heap containing *n* items is *O*(log(*n*)).
+    pub fn push(&mut self, item: T) {
+        self.heap.push(item);
+        // The new item may be equal to a tombstoned item, in which case it doesn't matter which of
+        // the two is discarded.
+        self.discard_dead_top();
+    }
+
+    /// Removes the greatest live item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.push(1);
+    /// heap.push(3);
+    /// heap.push(2);
+    /// heap.remove(&2);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop` on a heap containing *n* items is *O*(log(*n*)), plus
+    /// *O*(log(*n*)) for every tombstoned item it uncovers. It may also compact the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let item = self.heap.pop()?;
+        self.discard_dead_top();
+        // With fewer items stored, the tombstones may now be over the threshold.
+        self.compact_if_needed();
+        Some(item)
+    }
+
+    /// Removes an item equal to `item` from the heap.
+    ///
+    /// The item is only recorded as a tombstone, and stays in the heap until it reaches the top
+    /// or the heap is compacted. If tombstones now make up more than the
+    /// [compaction threshold](Self::compaction_threshold) of the stored items, the heap is
+    /// compacted right away.
+    ///
+    /// It is a logic error to remove an item that isn't in the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.push(1);
+    /// heap.push(3);
+    /// heap.push(3);
+    ///
+    /// // Only one of the equal items is removed.
+    /// heap.remove(&3);
+    /// assert_eq!(heap.len(), 2);
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Recording the tombstone takes *O*(1) time, but `remove` may also discard the top of the
+    /// heap or compact it. See the [type-level documentation](LazyDeleteHeap#time-complexity).
+    pub fn remove(&mut self, item: &T)
+    where
+        T: Clone,
+    {
+        match self.tombstones.get_mut(item) {
+            Some(count) => *count += 1,
+            None => {
+                self.tombstones.insert(item.clone(), 1);
+            }
+        }
+        self.tombstone_count += 1;
+        self.discard_dead_top();
+        self.compact_if_needed();
+    }
+
+    /// Sets the fraction of the stored items that tombstones may make up before the heap is
+    /// compacted.
+    ///
+    /// If tombstones already make up more than `threshold` of the stored items, the heap is
+    /// compacted right away. A threshold of 0.0 compacts the heap on every removal that doesn't
+    /// discard the top item. A threshold of 1.0 only compacts it once the tombstones outnumber the
+    /// stored items, which takes removing items that aren't in the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` isn't between 0.0 and 1.0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.extend([1, 2, 3, 4]);
+    ///
+    /// heap.set_compaction_threshold(0.0);
+    /// heap.remove(&2);
+    /// assert_eq!(heap.tombstones(), 0);
+    /// ```
+    pub fn set_compaction_threshold(&mut self, threshold: f64) {
+        assert!(
+            (0.0..=1.0).contains(&threshold),
+            "the compaction threshold must be between 0.0 and 1.0, got {threshold}"
+        );
+        self.compaction_threshold = threshold;
+        self.compact_if_needed();
+    }
+
+    /// Discards every tombstoned item from the heap, and rebuilds it from the live items.
+    ///
+    /// This is done automatically once tombstones make up more than the
+    /// [compaction threshold](Self::compaction_threshold) of the stored items.
+    ///
+    /// Any tombstones that are left over afterwards were recorded for items that weren't in the
+    /// heap. They are dropped, so that they don't remove items that are pushed later.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.extend([1, 2, 3, 4]);
+    /// heap.set_compaction_threshold(1.0);
+    /// heap.remove(&2);
+    /// assert_eq!(heap.tombstones(), 1);
+    ///
+    /// heap.compact();
+    /// assert_eq!(heap.tombstones(), 0);
+    /// assert_eq!(heap.len(), 3);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `compact` on a heap containing *n* items is *O*(*n*).
+    pub fn compact(&mut self) {
+        if self.tombstone_count > 0 {
+            let tombstones = &mut self.tombstones;
+            self.heap.retain(|item| match tombstones.get_mut(item) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            });
+            self.tombstones.clear();
+            self.tombstone_count = 0;
+        }
+    }
+
+    /// Consumes the heap and returns its live items in sorted (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.extend([4, 1, 8, 2]);
+    /// heap.remove(&4);
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 8]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        self.compact();
+        self.heap.into_sorted_vec()
+    }
+
+    /// Consumes the heap and returns its live items in arbitrary order.
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_vec(mut self) -> Vec<T> {
+        self.compact();
+        self.heap.into_vec()
+    }
+
+    fn compact_if_needed(&mut self) {
+        if self.tombstone_count as f64 > self.compaction_threshold * self.heap.len() as f64 {
+            self.compact();
+        }
+    }
+
+    /// Pops tombstoned items off the top of the heap, until the top item is live.
+    fn discard_dead_top(&mut self) {
+        while self.tombstone_count > 0 {
+            let Some(count) = self
+                .heap
+                .peek()
+                .and_then(|top| self.tombstones.get_mut(top))
+            else {
+                break;
+            };
+            *count -= 1;
+            if *count == 0 {
+                let top = self.heap.peek().expect("the top item was just peeked at");
+                self.tombstones.remove(top);
+            }
+            self.tombstone_count -= 1;
+            self.heap.pop();
+        }
+    }
+}
+
+impl<T, C> LazyDeleteHeap<T, C> {
+    /// Returns the greatest live item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.remove(&5);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Returns the number of live items in the heap, not counting tombstoned ones.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::LazyDeleteHeap;
+    /// let mut heap = LazyDeleteHeap::new();
+    /// heap.push(1);
+    /// heap.push(3);
+    /// heap.remove(&1);
+    ///
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heap.len() - self.tombstone_count
+    }
+
+    /// Checks if the heap has no live items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of tombstoned items that are still stored in the heap.
+    #[must_use]
+    pub fn tombstones(&self) -> usize {
+        self.tombstone_count
+    }
+
+    /// Returns the fraction of the stored items that tombstones may make up before the heap is
+    /// compacted. This defaults to 0.5.
+    #[must_use]
+    pub fn compaction_threshold(&self) -> f64 {
+        self.compaction_threshold
+    }
+
+    /// Drops all items and tombstones from the heap.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.tombstones.clear();
+        self.tombstone_count = 0;
+    }
+}
+
+impl<T: Ord + Hash> Default for LazyDeleteHeap<T> {
+    /// Creates an empty `LazyDeleteHeap<T>`.
+    fn default() -> Self {
+        LazyDeleteHeap::new()
+    }
+}
+
+impl<T: Clone, C: Clone> Clone for LazyDeleteHeap<T, C> {
+    fn clone(&self) -> Self {
+        LazyDeleteHeap {
+            heap: self.heap.clone(),
+            tombstones: self.tombstones.clone(),
+            tombstone_count: self.tombstone_count,
+            compaction_threshold: self.compaction_threshold,
+        }
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for LazyDeleteHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyDeleteHeap")
+            .field("items", &self.heap.as_slice())
+            .field("tombstones", &self.tombstones)
+            .finish()
+    }
+}
+
+impl<T: Ord + Hash> FromIterator<T> for LazyDeleteHeap<T> {
+    /// Builds a heap from the items of `iter`.
+    ///
+    /// This takes *O*(*n*) time.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        LazyDeleteHeap {
+            heap: BinaryHeap::from_iter(iter),
+            ..LazyDeleteHeap::new()
+        }
+    }
+}
+
+impl<T: Hash + Eq, C: Compare<T>> Extend<T> for LazyDeleteHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T: Hash + Eq, C: Compare<T>> PriorityQueue<T> for LazyDeleteHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
@@ -1,5 +1,96 @@
+//! A priority queue implemented with a binary heap, along with a [proptest]-based test suite that
+//! checks it against a naive reference implementation.
+//!
+//! The heap itself is a copy of the standard library's
+//! [`BinaryHeap`](std::collections::BinaryHeap), including the unstable
+//! [`drain_sorted`](BinaryHeap::drain_sorted) and
+//! [`into_iter_sorted`](BinaryHeap::into_iter_sorted) methods. See the [`binary_heap`] module for
+//! more.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::BinaryHeap;
+//!
+//! let mut heap = BinaryHeap::from([1, 5, 2]);
+//! heap.push(4);
+//!
+//! assert_eq!(heap.pop(), Some(5));
+//! assert_eq!(heap.into_sorted_vec(), [1, 2, 4]);
+//! ```
+//!
+//! # Cargo features
+//!
+//! * `std` (enabled by default): enables [`PriorityQueue`] and [`LazyDeleteHeap`], which hash
+//!   their items with the standard library's `RandomState`, [`DelayQueue`], which reads the time
+//!   from `Instant`, and [`SyncBinaryHeap`], [`MultiQueue`] and the async [`channel`], which share
+//!   heaps between threads with mutexes. Without this feature, the crate is `#![no_std]` and only
+//!   depends on `core` and `alloc`. [`ArrayHeap`] doesn't allocate at all.
+//! * `allocator-api2`: lets a heap allocate with a custom allocator, by keeping its elements in an
+//!   `allocator_api2::vec::Vec<T, A>`. This uses the stable mirror of the unstable allocator API
+//!   from the [`allocator-api2`](https://docs.rs/allocator-api2) crate, and adds
+//!   `BinaryHeap::new_in`, `BinaryHeap::with_capacity_in` and `BinaryHeap::allocator`.
+//! * `debug-invariants`: makes every method that changes a [`BinaryHeap`] check the heap property
+//!   when it returns with [`check_invariants`](BinaryHeap::check_invariants), and panic with the
+//!   [`HeapViolation`] if it doesn't hold. This turns each of those methods into an *O*(*n*)
+//!   operation, so it's meant for tests and debugging.
+//! * `proptest`: enables the `testing` module, which checks any implementation of
+//!   [`queue::PriorityQueue`] against a naive reference heap with [proptest], the same way this
+//!   crate tests its own heaps.
+//!
+//! [proptest]: https://docs.rs/proptest
+
+// Tests always link the standard library, both for the test harness and for proptest.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(unused_unsafe)]
 
-mod binary_heap;
+extern crate alloc;
+
+pub mod array_heap;
+pub mod binary_heap;
+#[cfg(feature = "std")]
+pub mod channel;
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod delay_queue;
+pub mod indexed_heap;
+#[cfg(feature = "std")]
+pub mod lazy_delete_heap;
+pub mod min_max_heap;
+#[cfg(feature = "std")]
+pub mod multi_queue;
+pub mod pairing_heap;
+#[cfg(feature = "std")]
+pub mod priority_queue;
+pub mod queue;
+pub mod stable_heap;
+#[cfg(feature = "std")]
+pub mod sync_heap;
+// The crate's own tests use the naive reference heap from `testing` as well.
+#[cfg(any(feature = "proptest", test))]
+pub mod testing;
 #[cfg(test)]
 mod tests;
+pub mod top_k;
+
+pub use crate::array_heap::ArrayHeap;
+pub use crate::binary_heap::{
+    BinaryHeap, DaryHeap, Drain, DrainSorted, HeapViolation, IntoIter, IntoIterSorted, Iter,
+    PeekMut,
+};
+pub use crate::compare::{Compare, FnComparator, KeyComparator, MaxComparator, MinComparator};
+#[cfg(feature = "std")]
+pub use crate::delay_queue::DelayQueue;
+pub use crate::indexed_heap::{Handle, IndexedHeap};
+#[cfg(feature = "std")]
+pub use crate::lazy_delete_heap::LazyDeleteHeap;
+pub use crate::min_max_heap::{MinMaxHeap, PeekMaxMut, PeekMinMut};
+#[cfg(feature = "std")]
+pub use crate::multi_queue::MultiQueue;
+pub use crate::pairing_heap::PairingHeap;
+#[cfg(feature = "std")]
+pub use crate::priority_queue::PriorityQueue;
+pub use crate::stable_heap::StableBinaryHeap;
+#[cfg(feature = "std")]
+pub use crate::sync_heap::SyncBinaryHeap;
+pub use crate::top_k::TopK;
@@ -0,0 +1,1066 @@
+//! A double-ended priority queue implemented with a [min-max heap].
+//!
+//! A min-max heap is a complete binary tree stored in a vector, like a binary heap. Elements on
+//! even levels (starting with the root) are smaller than all of their descendants, and elements on
+//! odd levels are greater than all of their descendants. This means that the smallest element is
+//! always at the root and the greatest is one of the root's children, so both ends of the queue
+//! can be inspected in *O*(1) time and removed in *O*(log(*n*)) time.
+//!
+//! [min-max heap]: https://en.wikipedia.org/wiki/Min-max_heap
+//!
+//! # Examples
+//!
+//! A bounded queue that evicts the worst item when it's full:
+//!
+//! ```
+//! use proptest_binary_heap_example::MinMaxHeap;
+//!
+//! let mut heap = MinMaxHeap::new();
+//! for score in [5, 1, 8, 3, 9, 2] {
+//!     heap.push(score);
+//!     if heap.len() > 3 {
+//!         heap.pop_min();
+//!     }
+//! }
+//!
+//! assert_eq!(heap.peek_min(), Some(&5));
+//! assert_eq!(heap.peek_max(), Some(&9));
+//! assert_eq!(heap.into_sorted_vec(), [5, 8, 9]);
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FromIterator, FusedIterator};
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+
+use alloc::vec::{self, Vec};
+
+use crate::compare::{Compare, MaxComparator};
+use crate::queue::PriorityQueue;
+
+/// A double-ended priority queue implemented with a min-max heap.
+///
+/// The smallest and greatest elements are determined by the comparator `C`, which defaults to the
+/// natural order of `T`.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the heap. The behavior resulting from such a logic
+/// error is not specified, but will not be undefined behavior.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_binary_heap_example::MinMaxHeap;
+///
+/// let mut heap = MinMaxHeap::from([3, 1, 4, 1, 5]);
+///
+/// assert_eq!(heap.pop_max(), Some(5));
+/// assert_eq!(heap.pop_min(), Some(1));
+/// assert_eq!(heap.pop_max(), Some(4));
+/// assert_eq!(heap.pop_min(), Some(1));
+/// assert_eq!(heap.pop_min(), Some(3));
+/// assert_eq!(heap.pop_min(), None);
+/// ```
+///
+/// # Time complexity
+///
+/// | [push]        | [pop\_min]/[pop\_max] | [peek\_min]/[peek\_max] |
+/// |---------------|-----------------------|-------------------------|
+/// | *O*(log(*n*)) | *O*(log(*n*))         | *O*(1)                  |
+///
+/// [push]: MinMaxHeap::push
+/// [pop\_min]: MinMaxHeap::pop_min
+/// [pop\_max]: MinMaxHeap::pop_max
+/// [peek\_min]: MinMaxHeap::peek_min
+/// [peek\_max]: MinMaxHeap::peek_max
+pub struct MinMaxHeap<T, C = MaxComparator> {
+    data: Vec<T>,
+    cmp: C,
+}
+
+/// Structure wrapping a mutable reference to the smallest item on a `MinMaxHeap`.
+///
+/// This `struct` is created by the [`peek_min_mut`] method on [`MinMaxHeap`]. See its
+/// documentation for more.
+///
+/// [`peek_min_mut`]: MinMaxHeap::peek_min_mut
+pub struct PeekMinMut<'a, T: 'a, C: 'a + Compare<T> = MaxComparator> {
+    heap: &'a mut MinMaxHeap<T, C>,
+    sift: bool,
+}
+
+impl<T: fmt::Debug, C: Compare<T>> fmt::Debug for PeekMinMut<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeekMinMut")
+            .field(&self.heap.data[0])
+            .finish()
+    }
+}
+
+impl<T, C: Compare<T>> Drop for PeekMinMut<'_, T, C> {
+    fn drop(&mut self) {
+        if self.sift {
+            self.heap.trickle_down(0);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Deref for PeekMinMut<'_, T, C> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.heap.data[0]
+    }
+}
+
+impl<T, C: Compare<T>> DerefMut for PeekMinMut<'_, T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.sift = true;
+        &mut self.heap.data[0]
+    }
+}
+
+impl<'a, T, C: Compare<T>> PeekMinMut<'a, T, C> {
+    /// Removes the peeked value from the heap and returns it.
+    pub fn pop(mut this: PeekMinMut<'a, T, C>) -> T {
+        this.sift = false;
+        this.heap.pop_min().unwrap()
+    }
+}
+
+/// Structure wrapping a mutable reference to the greatest item on a `MinMaxHeap`.
+///
+/// This `struct` is created by the [`peek_max_mut`] method on [`MinMaxHeap`]. See its
+/// documentation for more.
+///
+/// [`peek_max_mut`]: MinMaxHeap::peek_max_mut
+pub struct PeekMaxMut<'a, T: 'a, C: 'a + Compare<T> = MaxComparator> {
+    heap: &'a mut MinMaxHeap<T, C>,
+    pos: usize,
+    sift: bool,
+}
+
+impl<T: fmt::Debug, C: Compare<T>> fmt::Debug for PeekMaxMut<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeekMaxMut")
+            .field(&self.heap.data[self.pos])
+            .finish()
+    }
+}
+
+impl<T, C: Compare<T>> Drop for PeekMaxMut<'_, T, C> {
+    fn drop(&mut self) {
+        if self.sift {
+            self.heap.restore_max(self.pos);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Deref for PeekMaxMut<'_, T, C> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.heap.data[self.pos]
+    }
+}
+
+impl<T, C: Compare<T>> DerefMut for PeekMaxMut<'_, T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.sift = true;
+        &mut self.heap.data[self.pos]
+    }
+}
+
+impl<'a, T, C: Compare<T>> PeekMaxMut<'a, T, C> {
+    /// Removes the peeked value from the heap and returns it.
+    pub fn pop(mut this: PeekMaxMut<'a, T, C>) -> T {
+        this.sift = false;
+        this.heap.pop_max().unwrap()
+    }
+}
+
+impl<T: Clone, C: Clone> Clone for MinMaxHeap<T, C> {
+    fn clone(&self) -> Self {
+        MinMaxHeap {
+            data: self.data.clone(),
+            cmp: self.cmp.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.data.clone_from(&source.data);
+        self.cmp.clone_from(&source.cmp);
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for MinMaxHeap<T, C> {
+    /// Creates an empty `MinMaxHeap<T, C>`.
+    #[inline]
+    fn default() -> MinMaxHeap<T, C> {
+        MinMaxHeap::from_vec_cmp(Vec::new(), C::default())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for MinMaxHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+    /// Creates an empty `MinMaxHeap`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new() -> MinMaxHeap<T> {
+        MinMaxHeap::from_vec_cmp(Vec::new(), MaxComparator)
+    }
+
+    /// Creates an empty `MinMaxHeap` with a specific capacity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::with_capacity(10);
+    /// assert!(heap.capacity() >= 10);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> MinMaxHeap<T> {
+        MinMaxHeap::from_vec_cmp(Vec::with_capacity(capacity), MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> MinMaxHeap<T, C> {
+    /// Creates a `MinMaxHeap` out of the elements of `vec`, ordered by the comparator `cmp`.
+    ///
+    /// This conversion happens in-place, and has *O*(*n*) time complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, MinMaxHeap};
+    /// let heap = MinMaxHeap::from_vec_cmp(vec![3, -7, 5], KeyComparator(|x: &i32| x.abs()));
+    /// assert_eq!(heap.peek_min(), Some(&3));
+    /// assert_eq!(heap.peek_max(), Some(&-7));
+    /// ```
+    #[must_use]
+    pub fn from_vec_cmp(vec: Vec<T>, cmp: C) -> Self {
+        let mut heap = MinMaxHeap { data: vec, cmp };
+        heap.rebuild();
+        heap
+    }
+
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    /// assert_eq!(heap.peek_max(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek_max(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_pos().map(|pos| &self.data[pos])
+    }
+
+    /// Returns a mutable reference to the smallest item in the heap, or `None` if it is empty.
+    ///
+    /// Note: If the `PeekMinMut` value is leaked, the heap may be in an inconsistent state.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 5, 2]);
+    /// {
+    ///     let mut val = heap.peek_min_mut().unwrap();
+    ///     *val = 10;
+    /// }
+    /// assert_eq!(heap.peek_min(), Some(&2));
+    /// assert_eq!(heap.peek_max(), Some(&10));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// If the item is modified then the worst case time complexity is *O*(log(*n*)),
+    /// otherwise it's *O*(1).
+    pub fn peek_min_mut(&mut self) -> Option<PeekMinMut<'_, T, C>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMinMut {
+                heap: self,
+                sift: false,
+            })
+        }
+    }
+
+    /// Returns a mutable reference to the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// Note: If the `PeekMaxMut` value is leaked, the heap may be in an inconsistent state.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 5, 2]);
+    /// {
+    ///     let mut val = heap.peek_max_mut().unwrap();
+    ///     *val = 0;
+    /// }
+    /// assert_eq!(heap.peek_min(), Some(&0));
+    /// assert_eq!(heap.peek_max(), Some(&2));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// If the item is modified then the worst case time complexity is *O*(log(*n*)),
+    /// otherwise it's *O*(1).
+    pub fn peek_max_mut(&mut self) -> Option<PeekMaxMut<'_, T, C>> {
+        let pos = self.max_pos()?;
+        Some(PeekMaxMut {
+            heap: self,
+            pos,
+            sift: false,
+        })
+    }
+
+    /// Removes the smallest item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.pop_min(), Some(1));
+    /// assert_eq!(heap.pop_min(), Some(3));
+    /// assert_eq!(heap.pop_min(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop_min` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.remove_at(0)
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.pop_max(), Some(3));
+    /// assert_eq!(heap.pop_max(), Some(1));
+    /// assert_eq!(heap.pop_max(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop_max` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn pop_max(&mut self) -> Option<T> {
+        let pos = self.max_pos()?;
+        self.remove_at(pos)
+    }
+
+    /// Pushes an item onto the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// assert_eq!(heap.peek_max(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.bubble_up(self.len() - 1);
+    }
+
+    /// Pushes an item onto the heap, then removes the smallest item and returns it.
+    ///
+    /// If `item` is no greater than the smallest item in the heap, it is returned immediately and
+    /// the heap is not modified. This is more efficient than calling [`push`] followed by
+    /// [`pop_min`].
+    ///
+    /// [`push`]: MinMaxHeap::push
+    /// [`pop_min`]: MinMaxHeap::pop_min
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([2, 4]);
+    ///
+    /// assert_eq!(heap.push_pop_min(1), 1);
+    /// assert_eq!(heap.push_pop_min(3), 2);
+    /// assert_eq!(heap.into_sorted_vec(), [3, 4]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push_pop_min` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    #[must_use = "if you don't need the returned item, use `push` instead"]
+    pub fn push_pop_min(&mut self, mut item: T) -> T {
+        match self.data.first_mut() {
+            Some(min) if self.cmp.compare(&item, min) == Ordering::Greater => {
+                mem::swap(&mut item, min);
+                self.trickle_down(0);
+                item
+            }
+            _ => item,
+        }
+    }
+
+    /// Pushes an item onto the heap, then removes the greatest item and returns it.
+    ///
+    /// If `item` is no smaller than the greatest item in the heap, it is returned immediately and
+    /// the heap is not modified. This is more efficient than calling [`push`] followed by
+    /// [`pop_max`].
+    ///
+    /// [`push`]: MinMaxHeap::push
+    /// [`pop_max`]: MinMaxHeap::pop_max
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([2, 4]);
+    ///
+    /// assert_eq!(heap.push_pop_max(5), 5);
+    /// assert_eq!(heap.push_pop_max(3), 4);
+    /// assert_eq!(heap.into_sorted_vec(), [2, 3]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `push_pop_max` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    #[must_use = "if you don't need the returned item, use `push` instead"]
+    pub fn push_pop_max(&mut self, mut item: T) -> T {
+        match self.max_pos() {
+            Some(pos) if self.cmp.compare(&item, &self.data[pos]) == Ordering::Less => {
+                mem::swap(&mut item, &mut self.data[pos]);
+                self.restore_max(pos);
+                item
+            }
+            _ => item,
+        }
+    }
+
+    /// Consumes the `MinMaxHeap` and returns a vector in sorted (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::from([1, 2, 4, 5, 7]);
+    /// heap.push(6);
+    /// heap.push(3);
+    ///
+    /// let vec = heap.into_sorted_vec();
+    /// assert_eq!(vec, [1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop_min() {
+            vec.push(item);
+        }
+        vec
+    }
+
+    /// Clears the heap, returning an iterator over the removed elements in sorted order. The
+    /// iterator yields elements from the greatest to the smallest, and from the smallest to the
+    /// greatest when iterated from the back. If the iterator is dropped before being fully
+    /// consumed, it drops the remaining elements in heap order.
+    ///
+    /// The returned iterator keeps a mutable borrow on the heap to optimize its implementation.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::from([1, 2, 3, 4, 5]);
+    /// let mut drain = heap.drain_sorted();
+    ///
+    /// assert_eq!(drain.next(), Some(5));
+    /// assert_eq!(drain.next_back(), Some(1));
+    /// assert_eq!(drain.next(), Some(4));
+    /// drop(drain);
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, C> {
+        DrainSorted { inner: self }
+    }
+
+    /// Returns the index of the greatest element, which is either the root or one of its children.
+    fn max_pos(&self) -> Option<usize> {
+        match self.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.less(1, 2) { 2 } else { 1 }),
+        }
+    }
+
+    /// Returns true if `data[a] < data[b]` according to the comparator.
+    #[inline]
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.cmp.compare(&self.data[a], &self.data[b]) == Ordering::Less
+    }
+
+    /// Removes the element at `pos`, filling the gap with the last element.
+    fn remove_at(&mut self, pos: usize) -> Option<T> {
+        if pos >= self.len() {
+            return None;
+        }
+        let item = self.data.swap_remove(pos);
+        if pos < self.len() {
+            self.trickle_down(pos);
+        }
+        Some(item)
+    }
+
+    /// Restores the heap property after the greatest element, at `pos`, has been made smaller.
+    fn restore_max(&mut self, pos: usize) {
+        if pos == 0 {
+            // The only element in the heap.
+            return;
+        }
+        // The element may now be smaller than the minimum at the root. If so, it becomes the new
+        // minimum, and the old minimum is trickled down from the max level instead.
+        if self.less(pos, 0) {
+            self.data.swap(pos, 0);
+        }
+        self.trickle_down(pos);
+    }
+
+    /// Moves the element at `pos` up the heap after it has been added to the bottom.
+    fn bubble_up(&mut self, pos: usize) {
+        if pos == 0 {
+            return;
+        }
+        let parent = (pos - 1) / 2;
+        if is_min_level(pos) {
+            if self.less(parent, pos) {
+                self.data.swap(pos, parent);
+                self.bubble_up_by(parent, Ordering::Greater);
+            } else {
+                self.bubble_up_by(pos, Ordering::Less);
+            }
+        } else if self.less(pos, parent) {
+            self.data.swap(pos, parent);
+            self.bubble_up_by(parent, Ordering::Less);
+        } else {
+            self.bubble_up_by(pos, Ordering::Greater);
+        }
+    }
+
+    /// Moves the element at `pos` up through its grandparents while it compares as `order` to
+    /// them: `Less` on min levels and `Greater` on max levels.
+    fn bubble_up_by(&mut self, mut pos: usize, order: Ordering) {
+        while pos > 2 {
+            let grandparent = ((pos - 1) / 2 - 1) / 2;
+            if self.cmp.compare(&self.data[pos], &self.data[grandparent]) != order {
+                break;
+            }
+            self.data.swap(pos, grandparent);
+            pos = grandparent;
+        }
+    }
+
+    /// Moves the element at `pos` down the heap after it has been replaced.
+    fn trickle_down(&mut self, pos: usize) {
+        if is_min_level(pos) {
+            self.trickle_down_by(pos, Ordering::Less);
+        } else {
+            self.trickle_down_by(pos, Ordering::Greater);
+        }
+    }
+
+    /// Moves the element at `pos` down the heap, swapping it with the most extreme of its children
+    /// and grandchildren while that compares as `order` to it: `Less` on min levels and `Greater`
+    /// on max levels.
+    fn trickle_down_by(&mut self, mut pos: usize, order: Ordering) {
+        let len = self.len();
+        loop {
+            let first_child = 2 * pos + 1;
+            if first_child >= len {
+                return;
+            }
+            let first_grandchild = 2 * first_child + 1;
+
+            // Find the most extreme of the (up to) two children and four grandchildren.
+            let mut extreme = first_child;
+            let candidates = (first_child + 1..first_child + 2)
+                .chain(first_grandchild..first_grandchild + 4)
+                .take_while(|&i| i < len);
+            for i in candidates {
+                if self.cmp.compare(&self.data[i], &self.data[extreme]) == order {
+                    extreme = i;
+                }
+            }
+
+            if self.cmp.compare(&self.data[extreme], &self.data[pos]) != order {
+                return;
+            }
+            self.data.swap(extreme, pos);
+
+            if extreme < first_grandchild {
+                // Children are on the opposite kind of level, and have no descendants that the
+                // element could be out of order with.
+                return;
+            }
+            // The element now sits below a parent on the opposite kind of level, which it may be
+            // out of order with.
+            let parent = (extreme - 1) / 2;
+            if self.cmp.compare(&self.data[parent], &self.data[extreme]) == order {
+                self.data.swap(parent, extreme);
+            }
+            pos = extreme;
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let mut n = self.len() / 2;
+        while n > 0 {
+            n -= 1;
+            self.trickle_down(n);
+        }
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    ///
+    /// let mut a = MinMaxHeap::from([-10, 1, 2, 3, 3]);
+    /// let mut b = MinMaxHeap::from([-20, 5, 43]);
+    ///
+    /// a.append(&mut b);
+    ///
+    /// assert_eq!(a.into_sorted_vec(), [-20, -10, 1, 2, 3, 3, 5, 43]);
+    /// assert!(b.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        if self.len() < other.len() {
+            mem::swap(&mut self.data, &mut other.data);
+        }
+        for item in other.data.drain(..) {
+            self.data.push(item);
+            self.bubble_up(self.data.len() - 1);
+        }
+    }
+}
+
+impl<T, C> MinMaxHeap<T, C> {
+    /// Returns the smallest item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    /// assert_eq!(heap.peek_min(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns an iterator visiting all values in the underlying vector, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let heap = MinMaxHeap::from([1, 2, 3, 4]);
+    ///
+    /// // Print 1, 2, 3, 4 in arbitrary order
+    /// for x in heap.iter() {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Returns the number of elements the heap can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::with_capacity(100);
+    /// assert!(heap.capacity() >= 100);
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be inserted in the
+    /// `MinMaxHeap`. The collection may reserve more space to avoid frequent reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.reserve(100);
+    /// assert!(heap.capacity() >= 100);
+    /// heap.push(4);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    /// Consumes the `MinMaxHeap` and returns the underlying vector in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let heap = MinMaxHeap::from([1, 2, 3, 4, 5, 6, 7]);
+    /// let vec = heap.into_vec();
+    ///
+    /// // Will print in some order
+    /// for x in vec {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Returns the length of the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let heap = MinMaxHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::new();
+    ///
+    /// assert!(heap.is_empty());
+    ///
+    /// heap.push(3);
+    ///
+    /// assert!(!heap.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the heap, returning an iterator over the removed elements in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 3]);
+    ///
+    /// for x in heap.drain() {
+    ///     println!("{x}");
+    /// }
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> vec::Drain<'_, T> {
+        self.data.drain(..)
+    }
+
+    /// Drops all items from the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    /// let mut heap = MinMaxHeap::from([1, 3]);
+    ///
+    /// heap.clear();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+/// Returns true if `pos` is on a min level, i.e. an even level of the tree.
+#[inline]
+fn is_min_level(pos: usize) -> bool {
+    (pos + 1).ilog2() & 1 == 0
+}
+
+/// A draining iterator over the elements of a `MinMaxHeap`, in sorted order.
+///
+/// This `struct` is created by [`MinMaxHeap::drain_sorted()`]. See its documentation for more.
+///
+/// [`drain_sorted`]: MinMaxHeap::drain_sorted
+pub struct DrainSorted<'a, T, C: Compare<T> = MaxComparator> {
+    inner: &'a mut MinMaxHeap<T, C>,
+}
+
+impl<T: fmt::Debug, C: Compare<T>> fmt::Debug for DrainSorted<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DrainSorted").field(&self.inner).finish()
+    }
+}
+
+impl<'a, T, C: Compare<T>> Drop for DrainSorted<'a, T, C> {
+    /// Removes heap elements in heap order.
+    fn drop(&mut self) {
+        struct DropGuard<'r, 'a, T, C: Compare<T>>(&'r mut DrainSorted<'a, T, C>);
+
+        impl<'r, 'a, T, C: Compare<T>> Drop for DropGuard<'r, 'a, T, C> {
+            fn drop(&mut self) {
+                while self.0.inner.pop_max().is_some() {}
+            }
+        }
+
+        while let Some(item) = self.inner.pop_max() {
+            let guard = DropGuard(self);
+            drop(item);
+            mem::forget(guard);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for DrainSorted<'_, T, C> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.inner.pop_max()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let exact = self.inner.len();
+        (exact, Some(exact))
+    }
+}
+
+impl<T, C: Compare<T>> DoubleEndedIterator for DrainSorted<'_, T, C> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.pop_min()
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for DrainSorted<'_, T, C> {}
+
+impl<T, C: Compare<T>> FusedIterator for DrainSorted<'_, T, C> {}
+
+impl<T: Ord> From<Vec<T>> for MinMaxHeap<T> {
+    /// Converts a `Vec<T>` into a `MinMaxHeap<T>`.
+    ///
+    /// This conversion happens in-place, and has *O*(*n*) time complexity.
+    fn from(vec: Vec<T>) -> MinMaxHeap<T> {
+        MinMaxHeap::from_vec_cmp(vec, MaxComparator)
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for MinMaxHeap<T> {
+    /// ```
+    /// use proptest_binary_heap_example::MinMaxHeap;
+    ///
+    /// let mut h1 = MinMaxHeap::from([1, 4, 2, 3]);
+    /// let mut h2: MinMaxHeap<_> = [1, 4, 2, 3].into();
+    /// while let Some((a, b)) = h1.pop_min().zip(h2.pop_min()) {
+    ///     assert_eq!(a, b);
+    /// }
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        Self::from_iter(arr)
+    }
+}
+
+impl<T, C> From<MinMaxHeap<T, C>> for Vec<T> {
+    /// Converts a `MinMaxHeap<T, C>` into a `Vec<T>`.
+    ///
+    /// This conversion requires no data movement or allocation, and has constant time complexity.
+    fn from(heap: MinMaxHeap<T, C>) -> Vec<T> {
+        heap.data
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinMaxHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> MinMaxHeap<T> {
+        MinMaxHeap::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T, C> IntoIterator for MinMaxHeap<T, C> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    /// Creates a consuming iterator, that is, one that moves each value out of the heap in
+    /// arbitrary order. The heap cannot be used after calling this.
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a MinMaxHeap<T, C> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for MinMaxHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+/// The max end of the heap acts as the priority queue.
+impl<T, C: Compare<T>> PriorityQueue<T> for MinMaxHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_max()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek_max()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
+
+impl<'a, T: 'a + Copy, C: Compare<T>> Extend<&'a T> for MinMaxHeap<T, C> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
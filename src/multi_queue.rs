@@ -0,0 +1,348 @@
+//! A concurrent priority queue that trades exact ordering for low contention.
+//!
+//! A [`SyncBinaryHeap`](crate::SyncBinaryHeap) serializes every push and pop on a single mutex,
+//! which becomes the bottleneck once many threads share it. [`MultiQueue`] instead spreads its
+//! items over several independently locked [`BinaryHeap`] shards, following the MultiQueue design
+//! of Rihani, Sanders and Dementiev ("MultiQueues: Simple Relaxed Concurrent Priority Queues",
+//! SPAA 2015):
+//!
+//! * [`push`](MultiQueue::push) adds the item to a random shard, skipping shards whose lock is
+//!   currently held by another thread.
+//! * [`pop`](MultiQueue::pop) picks two random shards, and pops from the one whose greatest item
+//!   is greater.
+//!
+//! # Relaxation
+//!
+//! Because an item only competes with the items in the shards that `pop` looks at, the queue
+//! doesn't always return the greatest item. Exactly what is guaranteed:
+//!
+//! * Every pushed item is popped at most once, and every item is popped eventually if threads keep
+//!   popping: nothing is lost or duplicated.
+//! * The popped item is the greatest item in its shard at the time of the pop, and is at least as
+//!   great as the greatest item in a second shard, chosen uniformly at random. Items that end up
+//!   in the same shard are popped in order.
+//! * There is no deterministic bound on how many greater items are still in the queue: in the
+//!   worst case, all of them are in shards the pop didn't look at. On average, the popped item is
+//!   among the greatest `O(shards)` items, and the analysis in the paper above bounds the rank
+//!   with high probability in the same way.
+//! * With a single shard, the queue is an exact priority queue behind a mutex.
+//! * `pop` only returns `None` after it has found every shard empty. If no push runs concurrently
+//!   with it, that means the queue is empty.
+//!
+//! This makes `MultiQueue` a good fit for schedulers that want high-priority work to run soon,
+//! rather than strictly first.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::MultiQueue;
+//! use std::thread;
+//!
+//! let queue = MultiQueue::with_shards(4);
+//! thread::scope(|s| {
+//!     for t in 0..4 {
+//!         let queue = &queue;
+//!         s.spawn(move || {
+//!             for i in 0..100 {
+//!                 queue.push(t * 100 + i);
+//!             }
+//!         });
+//!     }
+//! });
+//!
+//! // Every item comes out exactly once, if not quite in order.
+//! let mut popped = Vec::new();
+//! while let Some(item) = queue.pop() {
+//!     popped.push(item);
+//! }
+//! popped.sort();
+//! assert_eq!(popped, (0..400).collect::<Vec<_>>());
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+use std::thread;
+
+use crate::binary_heap::BinaryHeap;
+use crate::compare::{Compare, MaxComparator};
+
+/// A relaxed concurrent priority queue made of several independently locked binary heaps.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`, but only approximately: see the [module documentation](self) for exactly how far the
+/// ordering is relaxed.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the queue. The behavior resulting from such a logic
+/// error is not specified, but will not be undefined behavior.
+pub struct MultiQueue<T, C = MaxComparator> {
+    shards: Box<[Mutex<BinaryHeap<T, C>>]>,
+}
+
+impl<T: Ord> MultiQueue<T> {
+    /// Creates an empty `MultiQueue` as a max-heap, with two shards per thread the machine can
+    /// run in parallel.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MultiQueue;
+    /// let queue = MultiQueue::new();
+    /// queue.push(4);
+    /// assert!(queue.shards() >= 2);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        let parallelism = thread::available_parallelism().map_or(1, |n| n.get());
+        MultiQueue::with_shards(2 * parallelism)
+    }
+
+    /// Creates an empty `MultiQueue` as a max-heap, with the given number of shards.
+    ///
+    /// More shards mean less contention, but a looser ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MultiQueue;
+    /// let queue = MultiQueue::with_shards(1);
+    /// queue.push(1);
+    /// queue.push(3);
+    ///
+    /// // With a single shard, the ordering is exact.
+    /// assert_eq!(queue.pop(), Some(3));
+    /// ```
+    #[must_use]
+    pub fn with_shards(shards: usize) -> Self {
+        MultiQueue::with_shards_and_cmp(shards, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Clone> MultiQueue<T, C> {
+    /// Creates an empty `MultiQueue` ordered by the comparator `cmp`, with the given number of
+    /// shards. Each shard gets its own clone of `cmp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{MinComparator, MultiQueue};
+    /// let queue = MultiQueue::with_shards_and_cmp(1, MinComparator);
+    /// queue.push(3);
+    /// queue.push(1);
+    /// assert_eq!(queue.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn with_shards_and_cmp(shards: usize, cmp: C) -> Self {
+        assert!(shards > 0, "a MultiQueue needs at least one shard");
+        let shards = (0..shards)
+            .map(|_| Mutex::new(BinaryHeap::from_vec_cmp(Vec::new(), cmp.clone())))
+            .collect();
+        MultiQueue { shards }
+    }
+}
+
+impl<T, C: Compare<T>> MultiQueue<T, C> {
+    /// Pushes an item onto a random shard of the queue.
+    ///
+    /// Shards that are locked by another thread are skipped, so this only blocks if every shard it
+    /// tries is busy.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MultiQueue;
+    /// let queue = MultiQueue::new();
+    /// queue.push(3);
+    /// queue.push(5);
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn push(&self, item: T) {
+        // Give up on finding an idle shard after as many tries as there are shards, and wait for
+        // the last one instead.
+        for _ in 1..self.shards.len() {
+            match self.shards[random_index(self.shards.len())].try_lock() {
+                Ok(mut shard) => return shard.push(item),
+                Err(TryLockError::Poisoned(err)) => return err.into_inner().push(item),
+                Err(TryLockError::WouldBlock) => {}
+            }
+        }
+        self.lock(random_index(self.shards.len())).push(item);
+    }
+
+    /// Removes a great item from the queue and returns it, or `None` if the queue is empty.
+    ///
+    /// The item is the greater of the greatest items of two random shards. See the
+    /// [module documentation](self) for how this relates to the greatest item in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MultiQueue;
+    /// let queue = MultiQueue::new();
+    /// queue.push(1);
+    /// queue.push(3);
+    ///
+    /// let mut popped = [queue.pop().unwrap(), queue.pop().unwrap()];
+    /// popped.sort();
+    /// assert_eq!(popped, [1, 3]);
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let len = self.shards.len();
+        if len > 1 {
+            let first = random_index(len);
+            // Pick a second shard that is different from the first one.
+            let second = (first + 1 + random_index(len - 1)) % len;
+            // Lock the two shards in index order, so that two pops can't deadlock.
+            let mut low = self.lock(first.min(second));
+            let mut high = self.lock(first.max(second));
+            let better = match (low.peek(), high.peek()) {
+                (Some(l), Some(h)) if low.comparator().compare(l, h).is_lt() => &mut high,
+                (Some(_), _) => &mut low,
+                (None, Some(_)) => &mut high,
+                (None, None) => {
+                    // Both shards are empty. Let go of them before looking at every shard.
+                    drop((low, high));
+                    return self.pop_any();
+                }
+            };
+            return better.pop();
+        }
+        self.pop_any()
+    }
+
+    /// Pops from the first non-empty shard, starting from a random one, to tell an empty queue
+    /// from a couple of empty shards.
+    fn pop_any(&self) -> Option<T> {
+        let len = self.shards.len();
+        let start = random_index(len);
+        (0..len).find_map(|offset| self.lock((start + offset) % len).pop())
+    }
+
+    /// Consumes the queue and returns all of its items in sorted (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::MultiQueue;
+    /// let queue = MultiQueue::with_shards(3);
+    /// queue.extend([4, 1, 8, 2]);
+    /// assert_eq!(queue.into_sorted_vec(), [1, 2, 4, 8]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut shards = self
+            .shards
+            .into_vec()
+            .into_iter()
+            .map(|shard| shard.into_inner().unwrap_or_else(PoisonError::into_inner));
+        let Some(mut heap) = shards.next() else {
+            return Vec::new();
+        };
+        for shard in shards {
+            heap.extend(shard.into_vec());
+        }
+        heap.into_sorted_vec()
+    }
+
+    /// Pushes every item of `iter` onto the queue.
+    ///
+    /// This takes `&self`, so unlike [`Extend`] it can be called while the queue is shared.
+    pub fn extend<I: IntoIterator<Item = T>>(&self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, C> MultiQueue<T, C> {
+    /// Returns the number of shards the queue spreads its items over.
+    #[must_use]
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the number of items in the queue.
+    ///
+    /// The shards are counted one at a time, so if other threads push or pop items at the same
+    /// time, the result is only an estimate.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        (0..self.shards.len()).map(|i| self.lock(i).len()).sum()
+    }
+
+    /// Checks if the queue is empty.
+    ///
+    /// The shards are checked one at a time, so if other threads push or pop items at the same
+    /// time, the result is only an estimate.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|i| self.lock(i).is_empty())
+    }
+
+    fn lock(&self, index: usize) -> MutexGuard<'_, BinaryHeap<T, C>> {
+        self.shards[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Ord> Default for MultiQueue<T> {
+    /// Creates an empty `MultiQueue<T>`, with two shards per thread the machine can run in
+    /// parallel.
+    fn default() -> Self {
+        MultiQueue::new()
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for MultiQueue<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Locking every shard in index order can't deadlock with `pop`.
+        let shards: Vec<_> = (0..self.shards.len()).map(|i| self.lock(i)).collect();
+        f.debug_list()
+            .entries(shards.iter().map(|shard| shard.as_slice()))
+            .finish()
+    }
+}
+
+/// Returns a random index below `len`, from a xorshift generator local to the current thread.
+///
+/// This doesn't need to be a good source of randomness, only cheap and different between threads.
+fn random_index(len: usize) -> usize {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(
+            // `RandomState` is seeded differently for every thread. The state must be non-zero.
+            std::collections::hash_map::RandomState::new().hash_one(0u8) | 1,
+        );
+    }
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x % len as u64) as usize
+    })
+}
@@ -0,0 +1,1011 @@
+//! A mergeable priority queue implemented with a [pairing heap].
+//!
+//! Unlike [`BinaryHeap`](crate::BinaryHeap), whose [`append`](crate::BinaryHeap::append) has to
+//! rebuild the combined heap, two pairing heaps can be melded in *O*(1) time. Pushing and peeking
+//! are also *O*(1), and popping is amortized *O*(log(*n*)).
+//!
+//! Every element lives in its own node, and [`push`](PairingHeap::push) returns a [`Handle`] to
+//! that node. The handle can later be used to move the element towards the top of the heap with
+//! [`decrease_key`](PairingHeap::decrease_key).
+//!
+//! [pairing heap]: https://en.wikipedia.org/wiki/Pairing_heap
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::PairingHeap;
+//!
+//! let mut a = PairingHeap::from([1, 5, 2]);
+//! let mut b = PairingHeap::new();
+//! let handle = b.push(3);
+//! b.push(4);
+//!
+//! a.meld(b);
+//! assert_eq!(a.len(), 5);
+//!
+//! // Move `3` to the top of the heap.
+//! assert_eq!(a.decrease_key(&handle, 10), Ok(3));
+//! assert_eq!(a.pop(), Some(10));
+//! assert_eq!(a.into_sorted_vec(), [1, 2, 4, 5]);
+//! ```
+
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FromIterator, FusedIterator};
+use core::mem;
+
+use alloc::rc::{Rc, Weak};
+use alloc::vec::Vec;
+
+use crate::compare::{Compare, MaxComparator, MinComparator};
+use crate::queue::PriorityQueue;
+
+/// A reference to an element in a [`PairingHeap`].
+///
+/// A handle stays valid until the element it refers to is popped, or the heap that contains it is
+/// dropped. After that, [`decrease_key`](PairingHeap::decrease_key) treats it as absent. Handles
+/// stay valid when their heap is melded into another heap, and then refer to the element in the
+/// combined heap.
+///
+/// A heap also treats a handle as absent if its element is in another heap.
+pub struct Handle<T>(Weak<Node<T>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.0.as_ptr()).finish()
+    }
+}
+
+/// A mergeable priority queue implemented with a pairing heap.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`.
+///
+/// Nodes are reference counted so that handles can detect when their element has left the heap.
+/// As a result, `PairingHeap` is neither `Send` nor `Sync`.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the heap, except through
+/// [`decrease_key`](Self::decrease_key). The behavior resulting from such a logic error is not
+/// specified, but will not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [push] | [pop]                   | [peek] | [meld] | [decrease\_key]         |
+/// |--------|-------------------------|--------|--------|-------------------------|
+/// | *O*(1) | *O*(log(*n*))~          | *O*(1) | *O*(1) | *O*(log(*n*))~          |
+///
+/// The value for `pop` and `decrease_key` is an amortized cost. `decrease_key` is conjectured to
+/// be faster in practice, but the tight bound for pairing heaps is an open problem.
+///
+/// [push]: PairingHeap::push
+/// [pop]: PairingHeap::pop
+/// [peek]: PairingHeap::peek
+/// [meld]: PairingHeap::meld
+/// [decrease\_key]: PairingHeap::decrease_key
+pub struct PairingHeap<T, C = MaxComparator> {
+    root: Option<Rc<Node<T>>>,
+    len: usize,
+    cmp: C,
+    owner: Rc<Owner>,
+}
+
+/// Identifies a heap, so that a handle to a node in another heap can be told apart.
+///
+/// Melding a heap into another forwards the owner of the first heap to the owner of the second,
+/// instead of updating every node, which keeps melding *O*(1). The first heap gets a new owner. The
+/// forwarding links are followed and shortened when a node's owner is looked up.
+#[derive(Default)]
+struct Owner {
+    forward: RefCell<Option<Rc<Owner>>>,
+}
+
+impl Owner {
+    /// Follows the forwarding links to the owner of the heap that currently contains the nodes of
+    /// `this`, and points every owner on the way straight at it.
+    fn find(this: &Rc<Owner>) -> Rc<Owner> {
+        let mut root = this.clone();
+        loop {
+            let next = root.forward.borrow().clone();
+            match next {
+                Some(next) => root = next,
+                None => break,
+            }
+        }
+        let mut cur = this.clone();
+        while !Rc::ptr_eq(&cur, &root) {
+            let next = cur.forward.replace(Some(root.clone()));
+            cur = next.expect("only the last owner doesn't forward");
+        }
+        root
+    }
+}
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        // Dropping the owners recursively could overflow the stack for long forwarding chains.
+        let mut next = self.forward.get_mut().take();
+        while let Some(owner) = next {
+            next = Rc::try_unwrap(owner)
+                .ok()
+                .and_then(|mut owner| owner.forward.get_mut().take());
+        }
+    }
+}
+
+/// A node in the heap. Children are stored as a singly-owned linked list starting at the leftmost
+/// child, with a back link so that a node can be cut out of the list in *O*(1) time.
+///
+/// Nodes are only ever mutated through a `&mut PairingHeap` that contains them, which
+/// [`decrease_key`](PairingHeap::decrease_key) checks through the owner, and borrows of the item
+/// and links never outlive the method that takes them. This is what makes the unguarded borrows in
+/// `&self` methods sound.
+struct Node<T> {
+    item: RefCell<T>,
+    links: RefCell<Links<T>>,
+    /// The owner of the heap the node was pushed into, which may forward to the current one.
+    owner: RefCell<Rc<Owner>>,
+}
+
+struct Links<T> {
+    /// The leftmost child.
+    child: Option<Rc<Node<T>>>,
+    /// The next sibling to the right.
+    next: Option<Rc<Node<T>>>,
+    /// The previous sibling, or the parent for the leftmost child. Dangling for the root.
+    prev: Weak<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(item: T, owner: Rc<Owner>) -> Rc<Self> {
+        Rc::new(Node {
+            item: RefCell::new(item),
+            links: RefCell::new(Links {
+                child: None,
+                next: None,
+                prev: Weak::new(),
+            }),
+            owner: RefCell::new(owner),
+        })
+    }
+
+    /// Checks if the node is in the heap with the given owner.
+    fn is_owned_by(&self, owner: &Rc<Owner>) -> bool {
+        let current = Owner::find(&self.owner.borrow());
+        let is_owned = Rc::ptr_eq(&current, owner);
+        *self.owner.borrow_mut() = current;
+        is_owned
+    }
+
+    /// Detaches the node from its siblings, returning the next sibling.
+    fn take_next(&self) -> Option<Rc<Node<T>>> {
+        let mut links = self.links.borrow_mut();
+        links.prev = Weak::new();
+        links.next.take()
+    }
+
+    fn into_item(this: Rc<Self>) -> T {
+        match Rc::try_unwrap(this) {
+            Ok(node) => node.item.into_inner(),
+            Err(_) => unreachable!("handles only hold weak references"),
+        }
+    }
+}
+
+/// Makes the detached tree `child` the leftmost child of `parent`.
+fn add_child<T>(parent: &Rc<Node<T>>, child: Rc<Node<T>>) {
+    let mut parent_links = parent.links.borrow_mut();
+    {
+        let mut child_links = child.links.borrow_mut();
+        if let Some(sibling) = parent_links.child.take() {
+            sibling.links.borrow_mut().prev = Rc::downgrade(&child);
+            child_links.next = Some(sibling);
+        }
+        child_links.prev = Rc::downgrade(parent);
+    }
+    parent_links.child = Some(child);
+}
+
+/// Detaches the leftmost child of `parent`, along with its subtree.
+fn take_child<T>(parent: &Rc<Node<T>>) -> Option<Rc<Node<T>>> {
+    let mut parent_links = parent.links.borrow_mut();
+    let child = parent_links.child.take()?;
+    parent_links.child = child.take_next();
+    if let Some(next) = &parent_links.child {
+        next.links.borrow_mut().prev = Rc::downgrade(parent);
+    }
+    Some(child)
+}
+
+/// Links two detached trees, making `b` the parent if `b_is_greater`, and the leftmost child of
+/// `a` otherwise.
+///
+/// The caller compares the roots beforehand, while the trees are still reachable from the heap, so
+/// that a panicking comparator can't drop them.
+fn link<T>(a: Rc<Node<T>>, b: Rc<Node<T>>, b_is_greater: bool) -> Rc<Node<T>> {
+    let (parent, child) = if b_is_greater { (b, a) } else { (a, b) };
+    add_child(&parent, child);
+    parent
+}
+
+fn is_greater<T, C: Compare<T>>(cmp: &C, a: &Node<T>, b: &Node<T>) -> bool {
+    cmp.compare(&a.item.borrow(), &b.item.borrow()) == Ordering::Greater
+}
+
+/// The children of a popped root, while [`merge`](Pairing::merge) combines them into a single tree
+/// using the standard two-pass pairing strategy: link adjacent pairs from left to right, then link
+/// the results from right to left.
+///
+/// The trees stay here while they are compared, so that they can be put back if a comparison
+/// panics.
+struct Pairing<T> {
+    /// The siblings that haven't been paired up yet.
+    rest: Option<Rc<Node<T>>>,
+    /// The trees linked so far.
+    pairs: Vec<Rc<Node<T>>>,
+}
+
+impl<T> Pairing<T> {
+    fn merge<C: Compare<T>>(&mut self, cmp: &C) -> Option<Rc<Node<T>>> {
+        while let Some(a) = &self.rest {
+            let b_is_greater = match &a.links.borrow().next {
+                Some(b) => is_greater(cmp, b, a),
+                None => false,
+            };
+            let a = self
+                .rest
+                .take()
+                .expect("the first sibling was just compared");
+            match a.take_next() {
+                Some(b) => {
+                    self.rest = b.take_next();
+                    self.pairs.push(link(a, b, b_is_greater));
+                }
+                None => self.pairs.push(a),
+            }
+        }
+        while let [.., node, acc] = self.pairs.as_slice() {
+            let acc_is_greater = is_greater(cmp, acc, node);
+            let acc = self.pairs.pop().expect("there are at least two trees");
+            let node = self.pairs.pop().expect("there are at least two trees");
+            self.pairs.push(link(node, acc, acc_is_greater));
+        }
+        self.pairs.pop()
+    }
+}
+
+/// Puts a popped root back into the heap if combining its children panics, with the trees that are
+/// left as its children. None of them compares greater than the root, so the heap stays in order.
+struct RestoreRoot<'a, T> {
+    slot: &'a mut Option<Rc<Node<T>>>,
+    root: Option<Rc<Node<T>>>,
+    pairing: Pairing<T>,
+}
+
+impl<T> Drop for RestoreRoot<'_, T> {
+    fn drop(&mut self) {
+        if let Some(root) = self.root.take() {
+            let mut rest = self.pairing.rest.take();
+            while let Some(tree) = rest {
+                rest = tree.take_next();
+                add_child(&root, tree);
+            }
+            for tree in self.pairing.pairs.drain(..) {
+                add_child(&root, tree);
+            }
+            *self.slot = Some(root);
+        }
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for PairingHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for PairingHeap<T, C> {
+    /// Creates an empty `PairingHeap<T, C>`.
+    #[inline]
+    fn default() -> Self {
+        PairingHeap::with_cmp(C::default())
+    }
+}
+
+impl<T, C> Drop for PairingHeap<T, C> {
+    fn drop(&mut self) {
+        // Dropping the nodes recursively could overflow the stack for long sibling lists.
+        drop(IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            len: self.len,
+        });
+    }
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Creates an empty `PairingHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        PairingHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T: Ord> PairingHeap<T, MinComparator> {
+    /// Creates an empty `PairingHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new_min();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        PairingHeap::with_cmp(MinComparator)
+    }
+}
+
+impl<T, C: Compare<T>> PairingHeap<T, C> {
+    /// Creates an empty `PairingHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, PairingHeap};
+    /// let mut heap = PairingHeap::with_cmp(KeyComparator(|x: &i32| x.abs()));
+    /// heap.push(3);
+    /// heap.push(-7);
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        PairingHeap {
+            root: None,
+            len: 0,
+            cmp,
+            owner: Rc::default(),
+        }
+    }
+
+    /// Pushes an item onto the heap, returning a handle that refers to it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    pub fn push(&mut self, item: T) -> Handle<T> {
+        let node = Node::new(item, self.owner.clone());
+        let handle = Handle(Rc::downgrade(&node));
+        self.len += 1;
+        self.meld_root(node);
+        handle
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// The handle that referred to the item becomes invalid.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The amortized cost of `pop` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        let rest = root.links.borrow_mut().child.take();
+        let mut guard = RestoreRoot {
+            slot: &mut self.root,
+            root: Some(root),
+            pairing: Pairing {
+                rest,
+                pairs: Vec::new(),
+            },
+        };
+        *guard.slot = guard.pairing.merge(&self.cmp);
+        let root = guard
+            .root
+            .take()
+            .expect("the root is only put back on a panic");
+        drop(guard);
+        self.len -= 1;
+        Some(Node::into_item(root))
+    }
+
+    /// Replaces the item that `handle` refers to with `item`, which must not compare less than the
+    /// current one, and returns the old item.
+    ///
+    /// This moves the element towards the top of the heap. The name follows the min-heap
+    /// convention of the literature: with [`new_min`](PairingHeap::new_min), `item` must be no
+    /// greater than the current item.
+    ///
+    /// Returns `Err(item)` and leaves the heap unchanged if the handle is no longer valid, if it
+    /// refers to an element of another heap, or if `item` compares less than the current item.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new_min();
+    /// let handle = heap.push(8);
+    /// heap.push(5);
+    ///
+    /// assert_eq!(heap.decrease_key(&handle, 9), Err(9));
+    /// assert_eq!(heap.decrease_key(&handle, 2), Ok(8));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.decrease_key(&handle, 1), Err(1));
+    ///
+    /// let mut other = PairingHeap::new_min();
+    /// let handle = other.push(4);
+    /// assert_eq!(heap.decrease_key(&handle, 3), Err(3));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The amortized cost of `decrease_key` on a heap containing *n* elements is
+    /// *O*(log(*n*)).
+    pub fn decrease_key(&mut self, handle: &Handle<T>, item: T) -> Result<T, T> {
+        let node = match handle.0.upgrade() {
+            Some(node) if node.is_owned_by(&self.owner) => node,
+            _ => return Err(item),
+        };
+        if self.cmp.compare(&item, &node.item.borrow()) == Ordering::Less {
+            return Err(item);
+        }
+        let old = node.item.replace(item);
+
+        let Some(prev) = node.links.borrow().prev.upgrade() else {
+            // The node is the root, so it is already at the top.
+            return Ok(old);
+        };
+        // Cut the node and its subtree out of its sibling list, then meld it back in at the root.
+        let next = node.take_next();
+        if let Some(next) = &next {
+            next.links.borrow_mut().prev = Rc::downgrade(&prev);
+        }
+        let cut = {
+            let mut prev_links = prev.links.borrow_mut();
+            let is_child = prev_links
+                .child
+                .as_ref()
+                .is_some_and(|child| Rc::ptr_eq(child, &node));
+            let slot = if is_child {
+                &mut prev_links.child
+            } else {
+                &mut prev_links.next
+            };
+            mem::replace(slot, next)
+        };
+        drop(cut);
+        self.meld_root(node);
+        Ok(old)
+    }
+
+    /// Returns true if the element that `handle` refers to is still in the heap.
+    ///
+    /// Returns false for a handle to an element of another heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new();
+    /// let handle = heap.push(3);
+    ///
+    /// assert!(heap.contains(&handle));
+    /// assert!(!PairingHeap::new().contains(&handle));
+    /// heap.pop();
+    /// assert!(!heap.contains(&handle));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        handle
+            .0
+            .upgrade()
+            .is_some_and(|node| node.is_owned_by(&self.owner))
+    }
+
+    /// Moves all the elements of `other` into `self`.
+    ///
+    /// Handles to elements of `other` stay valid, and refer to the elements in `self` afterwards.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    ///
+    /// let mut a = PairingHeap::from([-10, 1, 2, 3, 3]);
+    /// let b = PairingHeap::from([-20, 5, 43]);
+    ///
+    /// a.meld(b);
+    ///
+    /// assert_eq!(a.into_sorted_vec(), [-20, -10, 1, 2, 3, 3, 5, 43]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    pub fn meld(&mut self, mut other: Self) {
+        self.append(&mut other);
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// This is the same as [`meld`](PairingHeap::meld), but matches the signature of
+    /// [`BinaryHeap::append`](crate::BinaryHeap::append).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    ///
+    /// let mut a = PairingHeap::from([-10, 1, 2, 3, 3]);
+    /// let mut b = PairingHeap::from([-20, 5, 43]);
+    ///
+    /// a.append(&mut b);
+    ///
+    /// assert_eq!(a.into_sorted_vec(), [-20, -10, 1, 2, 3, 3, 5, 43]);
+    /// assert!(b.is_empty());
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some(root) = other.root.take() {
+            self.len += mem::take(&mut other.len);
+            *other.owner.forward.borrow_mut() = Some(self.owner.clone());
+            other.owner = Rc::default();
+            self.meld_root(root);
+        }
+    }
+
+    /// Consumes the `PairingHeap` and returns a vector in sorted (ascending) order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::from([1, 2, 4, 5, 7]);
+    /// heap.push(6);
+    /// heap.push(3);
+    ///
+    /// let vec = heap.into_sorted_vec();
+    /// assert_eq!(vec, [1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len);
+        while let Some(item) = self.pop() {
+            vec.push(item);
+        }
+        vec.reverse();
+        vec
+    }
+
+    /// Links a detached tree into the heap.
+    ///
+    /// The tree is made a child of the root first, and only moved above it once the comparison has
+    /// returned, so that a panicking comparator leaves it in the heap.
+    fn meld_root(&mut self, node: Rc<Node<T>>) {
+        let Some(root) = &self.root else {
+            self.root = Some(node);
+            return;
+        };
+        add_child(root, node);
+        let node_is_greater = {
+            let links = root.links.borrow();
+            let node = links.child.as_ref().expect("the node was just linked");
+            is_greater(&self.cmp, node, root)
+        };
+        if node_is_greater {
+            let root = self.root.take().expect("the root was just compared");
+            let node = take_child(&root).expect("the node was just linked");
+            add_child(&node, root);
+            self.root = Some(node);
+        }
+    }
+}
+
+impl<T, C> PairingHeap<T, C> {
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: nodes are only mutably borrowed through a `&mut PairingHeap` that contains them.
+        self.root
+            .as_ref()
+            .map(|root| unsafe { root.item.try_borrow_unguarded() }.unwrap())
+    }
+
+    /// Returns an iterator visiting all values in the heap, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let heap = PairingHeap::from([1, 2, 3, 4]);
+    ///
+    /// // Print 1, 2, 3, 4 in arbitrary order
+    /// for x in heap.iter() {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            len: self.len,
+        }
+    }
+
+    /// Consumes the `PairingHeap` and returns a vector with its items in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let heap = PairingHeap::from([1, 2, 3, 4, 5, 6, 7]);
+    /// let vec = heap.into_vec();
+    ///
+    /// // Will print in some order
+    /// for x in vec {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_vec(self) -> Vec<T> {
+        self.into_iter().collect()
+    }
+
+    /// Returns the length of the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let heap = PairingHeap::from([1, 3]);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::new();
+    ///
+    /// assert!(heap.is_empty());
+    ///
+    /// heap.push(3);
+    ///
+    /// assert!(!heap.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Drops all items from the heap, invalidating all handles.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let mut heap = PairingHeap::from([1, 3]);
+    ///
+    /// heap.clear();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        drop(IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            len: mem::take(&mut self.len),
+        });
+    }
+}
+
+/// An iterator over the elements of a `PairingHeap`.
+///
+/// This `struct` is created by [`PairingHeap::iter()`]. See its documentation for more.
+pub struct Iter<'a, T: 'a> {
+    stack: Vec<&'a Node<T>>,
+    len: usize,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.len -= 1;
+        // SAFETY: nodes are only mutably borrowed through a `&mut PairingHeap` that contains them,
+        // and the iterator borrows the heap immutably.
+        let (item, links) = unsafe {
+            (
+                node.item.try_borrow_unguarded().unwrap(),
+                node.links.try_borrow_unguarded().unwrap(),
+            )
+        };
+        self.stack.extend(links.next.as_deref());
+        self.stack.extend(links.child.as_deref());
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An owning iterator over the elements of a `PairingHeap`.
+///
+/// This `struct` is created by [`PairingHeap::into_iter()`] (provided by the [`IntoIterator`]
+/// trait). See its documentation for more.
+pub struct IntoIter<T> {
+    stack: Vec<Rc<Node<T>>>,
+    len: usize,
+}
+
+impl<T> fmt::Debug for IntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter").field("len", &self.len).finish()
+    }
+}
+
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.stack.pop()?;
+        self.len -= 1;
+        {
+            let mut links = node.links.borrow_mut();
+            self.stack.extend(links.next.take());
+            self.stack.extend(links.child.take());
+        }
+        Some(Node::into_item(node))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T: Ord> From<Vec<T>> for PairingHeap<T> {
+    /// Converts a `Vec<T>` into a `PairingHeap<T>`.
+    ///
+    /// This conversion has *O*(*n*) time complexity.
+    fn from(vec: Vec<T>) -> PairingHeap<T> {
+        PairingHeap::from_iter(vec)
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for PairingHeap<T> {
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    ///
+    /// let mut h1 = PairingHeap::from([1, 4, 2, 3]);
+    /// let mut h2: PairingHeap<_> = [1, 4, 2, 3].into();
+    /// while let Some((a, b)) = h1.pop().zip(h2.pop()) {
+    ///     assert_eq!(a, b);
+    /// }
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        Self::from_iter(arr)
+    }
+}
+
+impl<T, C> From<PairingHeap<T, C>> for Vec<T> {
+    /// Converts a `PairingHeap<T, C>` into a `Vec<T>`.
+    ///
+    /// The items are in arbitrary order.
+    fn from(heap: PairingHeap<T, C>) -> Vec<T> {
+        heap.into_vec()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PairingHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> PairingHeap<T> {
+        let mut heap = PairingHeap::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<T, C> IntoIterator for PairingHeap<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Creates a consuming iterator, that is, one that moves each value out of the heap in
+    /// arbitrary order. The heap cannot be used after calling this.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PairingHeap;
+    /// let heap = PairingHeap::from([1, 2, 3, 4]);
+    ///
+    /// // Print 1, 2, 3, 4 in arbitrary order
+    /// for x in heap.into_iter() {
+    ///     // x has type i32, not &i32
+    ///     println!("{x}");
+    /// }
+    /// ```
+    fn into_iter(mut self) -> IntoIter<T> {
+        IntoIter {
+            stack: self.root.take().into_iter().collect(),
+            len: mem::take(&mut self.len),
+        }
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a PairingHeap<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for PairingHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for PairingHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
+
+impl<'a, T: 'a + Copy, C: Compare<T>> Extend<&'a T> for PairingHeap<T, C> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
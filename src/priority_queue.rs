@@ -0,0 +1,580 @@
+//! A priority queue of keys, where the priority of every key can be looked up and changed.
+//!
+//! [`PriorityQueue`] stores each key at most once, along with its priority. Pushing a key that is
+//! already in the queue updates its priority instead of adding a second entry, so there is no need
+//! to keep stale entries around and skip them when they are popped.
+//!
+//! # Examples
+//!
+//! This is the [Dijkstra's algorithm][dijkstra] example from the [`binary_heap`](crate::binary_heap)
+//! module, rewritten to use a `PriorityQueue` with decrease-key instead of pushing duplicate nodes.
+//!
+//! [dijkstra]: https://en.wikipedia.org/wiki/Dijkstra%27s_algorithm
+//!
+//! ```
+//! use proptest_binary_heap_example::PriorityQueue;
+//!
+//! struct Edge {
+//!     node: usize,
+//!     cost: usize,
+//! }
+//!
+//! fn shortest_path(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
+//!     let mut dist: Vec<_> = (0..adj_list.len()).map(|_| usize::MAX).collect();
+//!     // A min-queue of nodes, keyed by node and prioritized by the current best cost.
+//!     let mut queue = PriorityQueue::new_min();
+//!
+//!     dist[start] = 0;
+//!     queue.push(start, 0);
+//!
+//!     while let Some((position, cost)) = queue.pop() {
+//!         if position == goal {
+//!             return Some(cost);
+//!         }
+//!
+//!         for edge in &adj_list[position] {
+//!             let next_cost = cost + edge.cost;
+//!             if next_cost < dist[edge.node] {
+//!                 // Either inserts the node or decreases its cost.
+//!                 queue.push(edge.node, next_cost);
+//!                 dist[edge.node] = next_cost;
+//!             }
+//!         }
+//!     }
+//!
+//!     None
+//! }
+//!
+//! let graph = vec![
+//!     vec![Edge { node: 2, cost: 10 }, Edge { node: 1, cost: 1 }],
+//!     vec![Edge { node: 3, cost: 2 }],
+//!     vec![Edge { node: 1, cost: 1 }, Edge { node: 3, cost: 3 }, Edge { node: 4, cost: 1 }],
+//!     vec![Edge { node: 0, cost: 7 }, Edge { node: 4, cost: 2 }],
+//!     vec![],
+//! ];
+//!
+//! assert_eq!(shortest_path(&graph, 0, 1), Some(1));
+//! assert_eq!(shortest_path(&graph, 0, 3), Some(3));
+//! assert_eq!(shortest_path(&graph, 3, 0), Some(7));
+//! assert_eq!(shortest_path(&graph, 0, 4), Some(5));
+//! assert_eq!(shortest_path(&graph, 4, 0), None);
+//! ```
+
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::Hash;
+
+use alloc::vec::Vec;
+
+use indexmap::IndexMap;
+
+use crate::binary_heap::{sift_down_range, sift_down_to_bottom, sift_up, Track};
+use crate::compare::{Compare, MaxComparator, MinComparator};
+
+/// A priority queue of unique keys, implemented with a binary heap over a hash map.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of the priorities `P`.
+///
+/// It is a logic error for a key to be modified in such a way that its hash or equality changes,
+/// or for a priority to be modified in such a way that its ordering relative to any other priority
+/// changes, while it is in the queue. The behavior resulting from such a logic error is not
+/// specified, but will not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [push]/[change\_priority] | [pop]/[remove] | [peek]/[priority] |
+/// |---------------------------|----------------|-------------------|
+/// | *O*(log(*n*))~            | *O*(log(*n*))~ | *O*(1)~           |
+///
+/// The values marked with ~ are expected costs, because they include a hash map lookup.
+///
+/// [push]: PriorityQueue::push
+/// [change\_priority]: PriorityQueue::change_priority
+/// [pop]: PriorityQueue::pop
+/// [remove]: PriorityQueue::remove
+/// [peek]: PriorityQueue::peek
+/// [priority]: PriorityQueue::priority
+pub struct PriorityQueue<K, P, C = MaxComparator> {
+    /// The keys and their priorities, in insertion order (modulo removals).
+    map: IndexMap<K, P>,
+    /// A heap of indexes into `map`.
+    heap: Vec<usize>,
+    /// For every index into `map`, the position of that index in `heap`.
+    positions: Vec<usize>,
+    cmp: C,
+}
+
+/// Compares indexes into the map by the priorities they refer to.
+struct ByPriority<'a, K, P, C> {
+    map: &'a IndexMap<K, P>,
+    cmp: &'a C,
+}
+
+impl<K, P, C: Compare<P>> Compare<usize> for ByPriority<'_, K, P, C> {
+    #[inline]
+    fn compare(&self, a: &usize, b: &usize) -> Ordering {
+        self.cmp.compare(&self.map[*a], &self.map[*b])
+    }
+}
+
+/// Updates `positions` as the sifting routines move indexes around.
+struct Positions<'a>(&'a mut [usize]);
+
+impl Track<usize> for Positions<'_> {
+    #[inline]
+    fn track(&mut self, elt: &usize, pos: usize) {
+        self.0[*elt] = pos;
+    }
+}
+
+impl<K: fmt::Debug, P: fmt::Debug, C> fmt::Debug for PriorityQueue<K, P, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K: Clone, P: Clone, C: Clone> Clone for PriorityQueue<K, P, C> {
+    fn clone(&self) -> Self {
+        PriorityQueue {
+            map: self.map.clone(),
+            heap: self.heap.clone(),
+            positions: self.positions.clone(),
+            cmp: self.cmp.clone(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, P, C: Compare<P> + Default> Default for PriorityQueue<K, P, C> {
+    /// Creates an empty `PriorityQueue<K, P, C>`.
+    #[inline]
+    fn default() -> Self {
+        PriorityQueue::with_cmp(C::default())
+    }
+}
+
+impl<K: Hash + Eq, P: Ord> PriorityQueue<K, P> {
+    /// Creates an empty `PriorityQueue` that pops the key with the greatest priority first.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 4);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        PriorityQueue::with_cmp(MaxComparator)
+    }
+}
+
+impl<K: Hash + Eq, P: Ord> PriorityQueue<K, P, MinComparator> {
+    /// Creates an empty `PriorityQueue` that pops the key with the smallest priority first.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new_min();
+    /// queue.push("a", 4);
+    /// queue.push("b", 2);
+    /// assert_eq!(queue.pop(), Some(("b", 2)));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        PriorityQueue::with_cmp(MinComparator)
+    }
+}
+
+impl<K: Hash + Eq, P, C: Compare<P>> PriorityQueue<K, P, C> {
+    /// Creates an empty `PriorityQueue` whose priorities are ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, PriorityQueue};
+    /// let mut queue = PriorityQueue::with_cmp(KeyComparator(|p: &(u32, u32)| p.1));
+    /// queue.push("a", (1, 2));
+    /// queue.push("b", (2, 1));
+    /// assert_eq!(queue.pop(), Some(("a", (1, 2))));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        PriorityQueue {
+            map: IndexMap::new(),
+            heap: Vec::new(),
+            positions: Vec::new(),
+            cmp,
+        }
+    }
+
+    /// Inserts `key` with the given priority. If the key is already in the queue, its priority is
+    /// replaced and the old priority is returned.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// assert_eq!(queue.push("a", 1), None);
+    /// assert_eq!(queue.push("b", 2), None);
+    /// assert_eq!(queue.push("a", 3), Some(1));
+    ///
+    /// assert_eq!(queue.len(), 2);
+    /// assert_eq!(queue.peek(), Some((&"a", &3)));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The expected cost of `push` is *O*(log(*n*)).
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if let Some(index) = self.map.get_index_of(&key) {
+            let old = core::mem::replace(&mut self.map[index], priority);
+            self.restore(self.positions[index]);
+            return Some(old);
+        }
+
+        let (index, _) = self.map.insert_full(key, priority);
+        let pos = self.heap.len();
+        self.heap.push(index);
+        self.positions.push(pos);
+        let cmp = ByPriority {
+            map: &self.map,
+            cmp: &self.cmp,
+        };
+        // SAFETY: Since we pushed a new item it means that
+        //  pos = self.heap.len() - 1 < self.heap.len()
+        unsafe {
+            sift_up::<2, _, _, _>(
+                &mut self.heap,
+                &cmp,
+                &mut Positions(&mut self.positions),
+                0,
+                pos,
+            )
+        };
+        None
+    }
+
+    /// Changes the priority of `key` if it is in the queue, returning the old priority. Returns
+    /// `None` and leaves the queue unchanged if `key` is not in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("b", 2);
+    ///
+    /// assert_eq!(queue.change_priority(&"a", 3), Some(1));
+    /// assert_eq!(queue.change_priority(&"c", 3), None);
+    /// assert_eq!(queue.pop(), Some(("a", 3)));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The expected cost of `change_priority` is *O*(log(*n*)).
+    pub fn change_priority<Q>(&mut self, key: &Q, priority: P) -> Option<P>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.get_index_of(key)?;
+        let old = core::mem::replace(&mut self.map[index], priority);
+        self.restore(self.positions[index]);
+        Some(old)
+    }
+
+    /// Removes the key with the greatest priority from the queue and returns it along with its
+    /// priority, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("b", 3);
+    ///
+    /// assert_eq!(queue.pop(), Some(("b", 3)));
+    /// assert_eq!(queue.pop(), Some(("a", 1)));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The expected cost of `pop` is *O*(log(*n*)).
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        let index = *self.heap.first()?;
+        Some(self.remove_index(index))
+    }
+
+    /// Removes `key` from the queue, returning it along with its priority, or `None` if it is not
+    /// in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("b", 3);
+    ///
+    /// assert_eq!(queue.remove(&"b"), Some(("b", 3)));
+    /// assert_eq!(queue.remove(&"b"), None);
+    /// assert_eq!(queue.pop(), Some(("a", 1)));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The expected cost of `remove` is *O*(log(*n*)).
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, P)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.get_index_of(key)?;
+        Some(self.remove_index(index))
+    }
+
+    /// Returns the priority of `key`, or `None` if it is not in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    ///
+    /// assert_eq!(queue.priority(&"a"), Some(&1));
+    /// assert_eq!(queue.priority(&"b"), None);
+    /// ```
+    #[must_use]
+    pub fn priority<Q>(&self, key: &Q) -> Option<&P>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns true if `key` is in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    ///
+    /// assert!(queue.contains_key(&"a"));
+    /// assert!(!queue.contains_key(&"b"));
+    /// ```
+    #[must_use]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Removes the entry at `index` in the map from both the map and the heap.
+    fn remove_index(&mut self, index: usize) -> (K, P) {
+        let pos = self.positions[index];
+        self.heap.swap_remove(pos);
+        if pos < self.heap.len() {
+            self.positions[self.heap[pos]] = pos;
+        }
+
+        // Removing from the map moves its last entry into `index`, so the heap entry that referred
+        // to the last entry has to be updated.
+        let entry = self.map.swap_remove_index(index).unwrap();
+        self.positions.swap_remove(index);
+        if index < self.map.len() {
+            self.heap[self.positions[index]] = index;
+        }
+
+        if pos < self.heap.len() {
+            if pos == 0 {
+                let cmp = ByPriority {
+                    map: &self.map,
+                    cmp: &self.cmp,
+                };
+                // SAFETY: pos == 0 < self.heap.len().
+                unsafe {
+                    sift_down_to_bottom::<2, _, _, _>(
+                        &mut self.heap,
+                        &cmp,
+                        &mut Positions(&mut self.positions),
+                        0,
+                    )
+                };
+            } else {
+                self.restore(pos);
+            }
+        }
+        entry
+    }
+
+    /// Moves the index at `pos` in the heap up or down after its priority has changed.
+    fn restore(&mut self, pos: usize) {
+        assert!(pos < self.heap.len());
+        let cmp = ByPriority {
+            map: &self.map,
+            cmp: &self.cmp,
+        };
+        let mut positions = Positions(&mut self.positions);
+        // SAFETY: pos < self.heap.len() was checked above.
+        let new_pos =
+            unsafe { sift_up::<2, _, _, _>(&mut self.heap, &cmp, &mut positions, 0, pos) };
+        if new_pos == pos {
+            let end = self.heap.len();
+            // SAFETY: pos < end == self.heap.len().
+            unsafe {
+                sift_down_range::<2, _, _, _>(&mut self.heap, &cmp, &mut positions, pos, end)
+            };
+        }
+    }
+}
+
+impl<K, P, C> PriorityQueue<K, P, C> {
+    /// Returns the key with the greatest priority along with its priority, or `None` if the queue
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// assert_eq!(queue.peek(), None);
+    ///
+    /// queue.push("a", 1);
+    /// queue.push("b", 5);
+    /// assert_eq!(queue.peek(), Some((&"b", &5)));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        let index = *self.heap.first()?;
+        self.map.get_index(index)
+    }
+
+    /// Returns an iterator visiting all keys and their priorities, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    ///
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), [(&"a", &1)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &P)> + '_ {
+        self.map.iter()
+    }
+
+    /// Returns the number of keys in the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("a", 2);
+    ///
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// assert!(queue.is_empty());
+    ///
+    /// queue.push("a", 1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all keys and priorities from the queue.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::PriorityQueue;
+    /// let mut queue = PriorityQueue::new();
+    /// queue.push("a", 1);
+    ///
+    /// queue.clear();
+    ///
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.positions.clear();
+        self.map.clear();
+    }
+}
+
+impl<K: Hash + Eq, P, C: Compare<P>> Extend<(K, P)> for PriorityQueue<K, P, C> {
+    fn extend<I: IntoIterator<Item = (K, P)>>(&mut self, iter: I) {
+        for (key, priority) in iter {
+            self.push(key, priority);
+        }
+    }
+}
+
+impl<K: Hash + Eq, P: Ord> FromIterator<(K, P)> for PriorityQueue<K, P> {
+    fn from_iter<I: IntoIterator<Item = (K, P)>>(iter: I) -> Self {
+        let mut queue = PriorityQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
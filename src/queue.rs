@@ -0,0 +1,63 @@
+//! A trait for the operations every priority queue in this crate has in common.
+//!
+//! [`PriorityQueue`] lets code work with any of the heaps in this crate, and with heaps from other
+//! crates that implement it. In particular, the `testing` module, enabled by the `proptest`
+//! feature, uses it to check any implementation against a naive reference.
+//!
+//! The trait isn't re-exported at the crate root, where `PriorityQueue` names the keyed queue from
+//! the `priority_queue` module. Refer to it as `queue::PriorityQueue`.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::queue::PriorityQueue;
+//! use proptest_binary_heap_example::{BinaryHeap, PairingHeap};
+//!
+//! fn top_three<Q: PriorityQueue<i32>>(mut queue: Q, items: &[i32]) -> Vec<i32> {
+//!     queue.extend(items.iter().copied());
+//!     std::iter::from_fn(|| queue.pop()).take(3).collect()
+//! }
+//!
+//! let items = [4, 9, 1, 7, 3];
+//! assert_eq!(top_three(BinaryHeap::new(), &items), [9, 7, 4]);
+//! assert_eq!(top_three(PairingHeap::new(), &items), [9, 7, 4]);
+//! ```
+
+use alloc::vec::Vec;
+
+/// A priority queue: a collection that hands out its greatest item first.
+///
+/// What "greatest" means is up to the implementation, which is usually ordered by a
+/// [`Compare`](crate::Compare) comparator. A min-heap's greatest item is its smallest one.
+///
+/// Pushing many items at once goes through the [`Extend`] supertrait.
+///
+/// The methods have the same names as the inherent methods of the heaps that implement the trait,
+/// which take precedence when both are available. Generic code only sees the trait methods.
+pub trait PriorityQueue<T>: Extend<T> {
+    /// Pushes an item onto the queue.
+    fn push(&mut self, item: T);
+
+    /// Removes the greatest item from the queue and returns it, or `None` if it is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns the greatest item in the queue, or `None` if it is empty.
+    fn peek(&self) -> Option<&T>;
+
+    /// Returns the number of items in the queue.
+    fn len(&self) -> usize;
+
+    /// Checks if the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consumes the queue and returns its items in sorted (ascending) order, so that the greatest
+    /// item is last.
+    ///
+    /// The order of equal items is up to the implementation, and need not be the reverse of the
+    /// order [`pop`](PriorityQueue::pop) would have returned them in.
+    fn into_sorted_vec(self) -> Vec<T>
+    where
+        Self: Sized;
+}
@@ -0,0 +1,455 @@
+//! A priority queue that pops equal items in the order they were pushed.
+//!
+//! [`BinaryHeap`] gives no guarantee about the order of items that compare equal. A
+//! [`StableBinaryHeap`] tags every item with a sequence number when it is pushed, and breaks ties
+//! in favor of the item that was pushed first, so that items of the same priority are served
+//! first-in, first-out.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::{KeyComparator, StableBinaryHeap};
+//!
+//! // Jobs are ordered by priority only.
+//! let mut jobs = StableBinaryHeap::with_cmp(KeyComparator(|job: &(u8, &str)| job.0));
+//! jobs.push((1, "backup"));
+//! jobs.push((5, "page on-call"));
+//! jobs.push((1, "rotate logs"));
+//! jobs.push((5, "restart service"));
+//!
+//! assert_eq!(jobs.pop(), Some((5, "page on-call")));
+//! assert_eq!(jobs.pop(), Some((5, "restart service")));
+//! assert_eq!(jobs.pop(), Some((1, "backup")));
+//! assert_eq!(jobs.pop(), Some((1, "rotate logs")));
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FromIterator, FusedIterator};
+
+use alloc::vec::Vec;
+
+use crate::binary_heap::{self, BinaryHeap};
+use crate::compare::{Compare, MaxComparator, MinComparator};
+use crate::queue::PriorityQueue;
+
+/// An item along with the sequence number it was pushed with.
+#[derive(Clone)]
+struct Entry<T> {
+    item: T,
+    seq: u64,
+}
+
+/// Orders entries by their items, and then in favor of the lower sequence number.
+#[derive(Clone)]
+struct StableComparator<C>(C);
+
+impl<T, C: Compare<T>> Compare<Entry<T>> for StableComparator<C> {
+    fn compare(&self, a: &Entry<T>, b: &Entry<T>) -> Ordering {
+        self.0
+            .compare(&a.item, &b.item)
+            .then_with(|| b.seq.cmp(&a.seq))
+    }
+}
+
+/// A priority queue that pops items which compare equal in the order they were pushed.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`. Every item is stored along with a 64-bit sequence number, which is never reused while
+/// the heap is not empty.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the heap. The behavior resulting from such a logic
+/// error is not specified, but will not be undefined behavior.
+///
+/// # Time complexity
+///
+/// | [push]  | [pop]         | [peek] |
+/// |---------|---------------|--------|
+/// | *O*(1)~ | *O*(log(*n*)) | *O*(1) |
+///
+/// The value for `push` is an expected cost, as for [`BinaryHeap::push`].
+///
+/// [push]: StableBinaryHeap::push
+/// [pop]: StableBinaryHeap::pop
+/// [peek]: StableBinaryHeap::peek
+pub struct StableBinaryHeap<T, C = MaxComparator> {
+    heap: BinaryHeap<Entry<T>, StableComparator<C>>,
+    next_seq: u64,
+}
+
+impl<T: Clone, C: Clone> Clone for StableBinaryHeap<T, C> {
+    fn clone(&self) -> Self {
+        StableBinaryHeap {
+            heap: self.heap.clone(),
+            next_seq: self.next_seq,
+        }
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for StableBinaryHeap<T, C> {
+    /// Creates an empty `StableBinaryHeap<T, C>`.
+    #[inline]
+    fn default() -> Self {
+        StableBinaryHeap::with_cmp(C::default())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for StableBinaryHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> StableBinaryHeap<T> {
+    /// Creates an empty `StableBinaryHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let mut heap = StableBinaryHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        StableBinaryHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T: Ord> StableBinaryHeap<T, MinComparator> {
+    /// Creates an empty `StableBinaryHeap` as a min-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let mut heap = StableBinaryHeap::new_min();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new_min() -> Self {
+        StableBinaryHeap::with_cmp(MinComparator)
+    }
+}
+
+impl<T, C: Compare<T>> StableBinaryHeap<T, C> {
+    /// Creates an empty `StableBinaryHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, StableBinaryHeap};
+    /// let mut heap = StableBinaryHeap::with_cmp(KeyComparator(|x: &i32| x.abs()));
+    /// heap.push(-7);
+    /// heap.push(7);
+    /// assert_eq!(heap.pop(), Some(-7));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        StableBinaryHeap {
+            heap: BinaryHeap::from_vec_cmp(Vec::new(), StableComparator(cmp)),
+            next_seq: 0,
+        }
+    }
+
+    /// Pushes an item onto the heap.
+    ///
+    /// The item will be popped after all items that compare equal to it and are already in the
+    /// heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let mut heap = StableBinaryHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The expected cost of `push` is *O*(1), and the worst case cost is *O*(log(*n*)), as for
+    /// [`BinaryHeap::push`].
+    pub fn push(&mut self, item: T) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Entry { item, seq });
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// Of several items that compare equal, the one that was pushed first is removed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let mut heap = StableBinaryHeap::from_iter([1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `pop` on a heap containing *n* elements is *O*(log(*n*)).
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|entry| entry.item)
+    }
+
+    /// Consumes the `StableBinaryHeap` and returns a vector in sorted (ascending) order.
+    ///
+    /// The sort is stable: items that compare equal are in the order they were pushed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{KeyComparator, StableBinaryHeap};
+    ///
+    /// let mut heap = StableBinaryHeap::with_cmp(KeyComparator(|x: &(u8, char)| x.0));
+    /// heap.extend([(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]);
+    ///
+    /// assert_eq!(heap.into_sorted_vec(), [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let (mut entries, StableComparator(cmp)) = self.heap.into_parts();
+        // Sequence numbers are unique, so an unstable sort is deterministic.
+        entries.sort_unstable_by(|a, b| {
+            cmp.compare(&a.item, &b.item)
+                .then_with(|| a.seq.cmp(&b.seq))
+        });
+        entries.into_iter().map(|entry| entry.item).collect()
+    }
+}
+
+impl<T, C> StableBinaryHeap<T, C> {
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    ///
+    /// Of several items that compare equal, this is the one that was pushed first.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let mut heap = StableBinaryHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|entry| &entry.item)
+    }
+
+    /// Returns an iterator visiting all items in the heap, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let heap = StableBinaryHeap::from_iter([1, 2, 3, 4]);
+    ///
+    /// // Print 1, 2, 3, 4 in arbitrary order
+    /// for x in heap.iter() {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.heap.iter(),
+        }
+    }
+
+    /// Consumes the `StableBinaryHeap` and returns its items in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let heap = StableBinaryHeap::from_iter([1, 2, 3]);
+    ///
+    /// let mut vec = heap.into_vec();
+    /// vec.sort();
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap
+            .into_vec()
+            .into_iter()
+            .map(|entry| entry.item)
+            .collect()
+    }
+
+    /// Returns the number of items in the heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::StableBinaryHeap;
+    /// let heap = StableBinaryHeap::from_iter([1, 3]);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if the heap is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Drops all items from the heap.
+    ///
+    /// This also restarts the sequence numbers, since there are no items left to order against.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.next_seq = 0;
+    }
+}
+
+impl<T: Ord> FromIterator<T> for StableBinaryHeap<T> {
+    /// Builds a heap from the items of `iter`, where items that compare equal are popped in the
+    /// order of the iterator.
+    ///
+    /// This takes *O*(*n*) time.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let entries: Vec<_> = iter
+            .into_iter()
+            .zip(0..)
+            .map(|(item, seq)| Entry { item, seq })
+            .collect();
+        let next_seq = entries.len() as u64;
+        StableBinaryHeap {
+            heap: BinaryHeap::from_vec_cmp(entries, StableComparator(MaxComparator)),
+            next_seq,
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for StableBinaryHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for StableBinaryHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a StableBinaryHeap<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// An iterator over the items of a `StableBinaryHeap`, in arbitrary order.
+///
+/// This `struct` is created by [`StableBinaryHeap::iter()`]. See its documentation for more.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Iter<'a, T> {
+    iter: binary_heap::Iter<'a, Entry<T>>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter")
+            .field(&self.clone().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|entry| &entry.item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.iter.next_back().map(|entry| &entry.item)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
@@ -0,0 +1,382 @@
+//! A thread-safe priority queue that blocks consumers until there is something to pop.
+//!
+//! [`SyncBinaryHeap`] wraps a [`BinaryHeap`] in a [`Mutex`], and uses [`Condvar`]s to wake up
+//! threads that are waiting for an item, or for room to push one into a bounded queue. It can be
+//! shared between threads through an `Arc`, or borrowed by scoped threads.
+//!
+//! Closing the queue with [`close`](SyncBinaryHeap::close) makes every further push fail. Threads
+//! that pop keep receiving the remaining items in priority order, and then `None` once the queue
+//! is empty, which is how a pool of workers learns that it can shut down.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::SyncBinaryHeap;
+//! use std::thread;
+//!
+//! let queue = SyncBinaryHeap::new();
+//!
+//! let total = thread::scope(|s| {
+//!     let worker = s.spawn(|| {
+//!         let mut total = 0;
+//!         while let Some(job) = queue.pop() {
+//!             total += job;
+//!         }
+//!         total
+//!     });
+//!
+//!     for job in 1..=10 {
+//!         queue.push(job).unwrap();
+//!     }
+//!     queue.close();
+//!     worker.join().unwrap()
+//! });
+//!
+//! assert_eq!(total, 55);
+//! ```
+
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::binary_heap::BinaryHeap;
+use crate::compare::{Compare, MaxComparator};
+
+/// A thread-safe priority queue implemented with a binary heap behind a mutex.
+///
+/// This will be a max-heap with respect to the comparator `C`, which defaults to the natural order
+/// of `T`. The queue is either unbounded, or holds at most a fixed number of items, in which case
+/// [`push`](SyncBinaryHeap::push) blocks while it is full.
+///
+/// If a comparator panics while the lock is held, the mutex is poisoned. The heap is still in a
+/// consistent state at that point, so the queue ignores the poisoning and keeps working.
+///
+/// It is a logic error for an item to be modified in such a way that the item's ordering relative
+/// to any other item changes while it is in the queue. The behavior resulting from such a logic
+/// error is not specified, but will not be undefined behavior.
+pub struct SyncBinaryHeap<T, C = MaxComparator> {
+    state: Mutex<State<T, C>>,
+    /// Signaled when an item is pushed, or the queue is closed.
+    not_empty: Condvar,
+    /// Signaled when an item is popped from a bounded queue, or the queue is closed.
+    not_full: Condvar,
+    capacity: Option<usize>,
+}
+
+struct State<T, C> {
+    heap: BinaryHeap<T, C>,
+    closed: bool,
+}
+
+impl<T: Ord> SyncBinaryHeap<T> {
+    /// Creates an empty, unbounded `SyncBinaryHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::new();
+    /// queue.push(4).unwrap();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        SyncBinaryHeap::with_cmp(MaxComparator)
+    }
+
+    /// Creates an empty `SyncBinaryHeap` as a max-heap that holds at most `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero, since nothing could ever be pushed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::bounded(16);
+    /// queue.push(4).unwrap();
+    /// assert_eq!(queue.capacity(), Some(16));
+    /// ```
+    #[must_use]
+    pub fn bounded(capacity: usize) -> Self {
+        SyncBinaryHeap::bounded_with_cmp(capacity, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> SyncBinaryHeap<T, C> {
+    /// Creates an empty, unbounded `SyncBinaryHeap` ordered by the comparator `cmp`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::{MinComparator, SyncBinaryHeap};
+    /// let queue = SyncBinaryHeap::with_cmp(MinComparator);
+    /// queue.push(3).unwrap();
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.try_pop(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        SyncBinaryHeap::from_parts(BinaryHeap::from_vec_cmp(Vec::new(), cmp), None)
+    }
+
+    /// Creates an empty `SyncBinaryHeap` ordered by the comparator `cmp`, that holds at most
+    /// `capacity` items.
+    ///
+    /// The backing heap is allocated up front with room for `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero, since nothing could ever be pushed.
+    #[must_use]
+    pub fn bounded_with_cmp(capacity: usize, cmp: C) -> Self {
+        assert!(
+            capacity > 0,
+            "a bounded SyncBinaryHeap needs a capacity of at least 1"
+        );
+        let heap = BinaryHeap::from_vec_cmp(Vec::with_capacity(capacity), cmp);
+        SyncBinaryHeap::from_parts(heap, Some(capacity))
+    }
+
+    fn from_parts(heap: BinaryHeap<T, C>, capacity: Option<usize>) -> Self {
+        SyncBinaryHeap {
+            state: Mutex::new(State {
+                heap,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity,
+        }
+    }
+
+    /// Pushes an item onto the queue, waking up a thread that is waiting to pop.
+    ///
+    /// If the queue is bounded and full, this blocks until another thread pops an item.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(item)` if the queue is closed, including while this was waiting for room.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::new();
+    /// assert_eq!(queue.push(1), Ok(()));
+    ///
+    /// queue.close();
+    /// assert_eq!(queue.push(2), Err(2));
+    /// ```
+    pub fn push(&self, item: T) -> Result<(), T> {
+        let state = self.lock();
+        let mut state = match self.capacity {
+            Some(capacity) => self
+                .not_full
+                .wait_while(state, |state| !state.closed && state.heap.len() >= capacity)
+                .unwrap_or_else(PoisonError::into_inner),
+            None => state,
+        };
+        if state.closed {
+            return Err(item);
+        }
+        state.heap.push(item);
+        drop(state);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Removes the greatest item from the queue and returns it, blocking until there is one.
+    ///
+    /// Returns `None` once the queue is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::new();
+    /// queue.push(1).unwrap();
+    /// queue.push(3).unwrap();
+    /// queue.close();
+    ///
+    /// assert_eq!(queue.pop(), Some(3));
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let state = self
+            .not_empty
+            .wait_while(self.lock(), |state| !state.closed && state.heap.is_empty())
+            .unwrap_or_else(PoisonError::into_inner);
+        self.pop_locked(state)
+    }
+
+    /// Removes the greatest item from the queue and returns it, blocking for at most `timeout`
+    /// until there is one.
+    ///
+    /// Returns `None` if the timeout elapses first, or once the queue is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// use std::time::Duration;
+    ///
+    /// let queue = SyncBinaryHeap::new();
+    /// assert_eq!(queue.pop_timeout(Duration::from_millis(1)), None);
+    ///
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.pop_timeout(Duration::from_millis(1)), Some(1));
+    /// ```
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let (state, _) = self
+            .not_empty
+            .wait_timeout_while(self.lock(), timeout, |state| {
+                !state.closed && state.heap.is_empty()
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        // Even if the wait timed out, an item may have arrived just in time.
+        self.pop_locked(state)
+    }
+
+    /// Removes the greatest item from the queue and returns it, or `None` if it is empty. This
+    /// never blocks, other than to take the lock.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::new();
+    /// assert_eq!(queue.try_pop(), None);
+    ///
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.try_pop(), Some(1));
+    /// ```
+    pub fn try_pop(&self) -> Option<T> {
+        self.pop_locked(self.lock())
+    }
+
+    fn pop_locked(&self, mut state: MutexGuard<'_, State<T, C>>) -> Option<T> {
+        let item = state.heap.pop();
+        drop(state);
+        if item.is_some() && self.capacity.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+
+    /// Consumes the queue and returns the heap inside it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// let queue = SyncBinaryHeap::new();
+    /// queue.push(1).unwrap();
+    /// queue.push(2).unwrap();
+    ///
+    /// assert_eq!(queue.into_heap().into_sorted_vec(), [1, 2]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_heap(self) -> BinaryHeap<T, C> {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .heap
+    }
+}
+
+impl<T, C> SyncBinaryHeap<T, C> {
+    /// Closes the queue.
+    ///
+    /// Every further push fails, and so does every push that is blocked waiting for room. Threads
+    /// that are blocked in [`pop`](SyncBinaryHeap::pop) are woken up, and receive the remaining
+    /// items followed by `None`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::SyncBinaryHeap;
+    /// use std::thread;
+    ///
+    /// let queue = SyncBinaryHeap::<i32>::new();
+    /// thread::scope(|s| {
+    ///     let waiter = s.spawn(|| queue.pop());
+    ///     queue.close();
+    ///     assert_eq!(waiter.join().unwrap(), None);
+    /// });
+    /// ```
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// Returns true if the queue has been closed.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Returns the maximum number of items the queue holds, or `None` if it is unbounded.
+    #[must_use]
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of items in the queue.
+    ///
+    /// Other threads may push or pop items at any time, so the result is only a snapshot.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().heap.len()
+    }
+
+    /// Checks if the queue is empty.
+    ///
+    /// Other threads may push or pop items at any time, so the result is only a snapshot.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().heap.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T, C>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Ord> Default for SyncBinaryHeap<T> {
+    /// Creates an empty, unbounded `SyncBinaryHeap<T>`.
+    fn default() -> Self {
+        SyncBinaryHeap::new()
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for SyncBinaryHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("SyncBinaryHeap")
+            .field("items", &state.heap)
+            .field("closed", &state.closed)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
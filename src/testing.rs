@@ -0,0 +1,426 @@
+//! Model-based tests for any [`PriorityQueue`] implementation.
+//!
+//! This is the test this crate runs against its own heaps, packaged so that other crates (and new
+//! heaps in this one) can run it too. It generates a random sequence of [`Op`]s, applies each one
+//! to both the queue under test and a [`NaiveHeap`] that is trivially correct by inspection, and
+//! checks after every operation that the two agree on what they popped, their length, and their
+//! greatest item. At the end, the sorted contents of both must match.
+//!
+//! [`check_priority_queue`] runs the whole test with proptest's default configuration, which
+//! honors the usual `PROPTEST_*` environment variables. To run it with a different configuration,
+//! or as part of a larger test, use [`Op::strategy`] and [`TestState`] directly.
+//!
+//! This module needs the `proptest` feature.
+//!
+//! # Equal items
+//!
+//! The naive heap can't know which of several items that compare equal a queue should pop first,
+//! so the test expects items that compare equal to also be equal according to [`PartialEq`]. The
+//! natural order of integers satisfies this, while a [`KeyComparator`](crate::KeyComparator)
+//! usually doesn't.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use proptest_binary_heap_example::{testing, BinaryHeap, MinComparator};
+//!
+//! testing::check_priority_queue(BinaryHeap::new, any::<u8>());
+//! testing::check_priority_queue_with_cmp(BinaryHeap::new_min, MinComparator, any::<u8>());
+//! ```
+//!
+//! Running the test as part of a `proptest!` block:
+//!
+//! ```
+//! use proptest::collection::vec;
+//! use proptest::prelude::*;
+//! use proptest_binary_heap_example::testing::{Op, TestState};
+//! use proptest_binary_heap_example::PairingHeap;
+//!
+//! proptest! {
+//!     # #![proptest_config(ProptestConfig::with_cases(16))]
+//!     fn test_pairing_heap(ops in vec(Op::strategy(0u16..100), 0..64)) {
+//!         let mut state = TestState::new(PairingHeap::new());
+//!         state.apply_ops(ops)?;
+//!         state.check_final()?;
+//!     }
+//! }
+//! # test_pairing_heap();
+//! ```
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{TestCaseError, TestRunner};
+
+use crate::compare::{Compare, MaxComparator};
+use crate::queue::PriorityQueue;
+
+/// This is a really simple "naive" heap that is trivially correct by inspection.
+///
+/// The time complexity of the `push` operation is `O(n log n)` which makes this infeasible to use
+/// in production, but it is perfect for tests.
+///
+/// Like the heaps in this crate, it is a max-heap with respect to the comparator `C`, which
+/// defaults to the natural order of `T`.
+#[derive(Clone)]
+pub struct NaiveHeap<T, C = MaxComparator> {
+    // The invariant here is that the data is always in sorted (ascending) order.
+    pub(crate) data: Vec<T>,
+    cmp: C,
+}
+
+impl<T: Ord> NaiveHeap<T> {
+    /// Creates an empty `NaiveHeap` as a max-heap.
+    #[must_use]
+    pub fn new() -> Self {
+        NaiveHeap::with_cmp(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> NaiveHeap<T, C> {
+    /// Creates an empty `NaiveHeap` ordered by the comparator `cmp`.
+    #[must_use]
+    pub fn with_cmp(cmp: C) -> Self {
+        NaiveHeap {
+            data: Vec::new(),
+            cmp,
+        }
+    }
+
+    /// Pushes an item onto the heap.
+    pub fn push(&mut self, item: T) {
+        // Push the item to the end.
+        self.data.push(item);
+        // Sort the vector.
+        self.sort();
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        // Data is always in sorted order so last element is greatest.
+        self.data.pop()
+    }
+
+    /// Pushes an item onto the heap, then removes the greatest item and returns it.
+    pub fn push_pop(&mut self, item: T) -> T {
+        self.push(item);
+        self.pop().expect("heap is non-empty after a push")
+    }
+
+    /// Removes the greatest item from the heap, then pushes an item onto it.
+    pub fn replace(&mut self, item: T) -> Option<T> {
+        let popped = self.pop();
+        self.push(item);
+        popped
+    }
+
+    /// Replaces the greatest item with `item`, the way writing through a `PeekMut` does. Does
+    /// nothing if the heap is empty.
+    pub fn replace_top(&mut self, item: T) {
+        if self.pop().is_some() {
+            self.push(item);
+        }
+    }
+
+    /// Consumes the heap and returns a vector in sorted (ascending) order.
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // self.data is already sorted so it's as simple as returning it
+        self.data
+    }
+
+    fn sort(&mut self) {
+        let cmp = &self.cmp;
+        self.data.sort_by(|a, b| cmp.compare(a, b));
+    }
+}
+
+impl<T, C> NaiveHeap<T, C> {
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        // Data is always in sorted order so last element is greatest.
+        self.data.last()
+    }
+
+    /// Returns the number of items in the heap.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks if the heap is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the items in the heap in sorted (ascending) order.
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Retains only the items specified by the predicate.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        // Removing items doesn't change the order of the rest.
+        self.data.retain(f);
+    }
+
+    /// Removes all items from the heap and returns them in sorted (ascending) order.
+    pub fn drain(&mut self) -> Vec<T> {
+        core::mem::take(&mut self.data)
+    }
+}
+
+impl<T: Ord> Default for NaiveHeap<T> {
+    /// Creates an empty `NaiveHeap<T>`.
+    fn default() -> Self {
+        NaiveHeap::new()
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for NaiveHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.data).finish()
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for NaiveHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.data.extend(iter);
+        self.sort();
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for NaiveHeap<T, C> {
+    fn push(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec()
+    }
+}
+
+/// An operation on a priority queue, covering every mutating method of [`PriorityQueue`].
+#[derive(Clone, Debug)]
+pub enum Op<T> {
+    /// Pushes an item with [`PriorityQueue::push`].
+    Push(T),
+    /// Pops the greatest item with [`PriorityQueue::pop`].
+    Pop,
+    /// Pushes a few items at once with [`Extend::extend`].
+    Extend(Vec<T>),
+}
+
+impl<T: Clone + fmt::Debug> Op<T> {
+    /// Returns a strategy for operations whose items are drawn from `items`.
+    ///
+    /// Pushes and pops are several times as likely as extends, so that the queue has items to
+    /// work on without growing too quickly.
+    pub fn strategy<S>(items: S) -> impl Strategy<Value = Op<T>>
+    where
+        S: Strategy<Value = T> + Clone,
+    {
+        prop_oneof![
+            4 => items.clone().prop_map(Op::Push),
+            4 => Just(Op::Pop),
+            1 => vec(items, 0..4).prop_map(Op::Extend),
+        ]
+    }
+}
+
+/// The state of a model-based test: the queue under test and the naive heap that acts as a
+/// baseline.
+#[derive(Debug)]
+pub struct TestState<Q, T, C = MaxComparator> {
+    /// The queue under test.
+    pub queue: Q,
+    /// The naive heap that the queue is checked against.
+    pub naive: NaiveHeap<T, C>,
+}
+
+impl<Q: PriorityQueue<T>, T: Ord + Clone + fmt::Debug> TestState<Q, T> {
+    /// Creates a new `TestState` for a max-heap with respect to the natural order of `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queue` isn't empty.
+    #[must_use]
+    pub fn new(queue: Q) -> Self {
+        TestState::with_cmp(queue, MaxComparator)
+    }
+}
+
+impl<Q, T, C> TestState<Q, T, C>
+where
+    Q: PriorityQueue<T>,
+    T: Clone + fmt::Debug + PartialEq,
+    C: Compare<T>,
+{
+    /// Creates a new `TestState` for a queue that is ordered by the comparator `cmp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queue` isn't empty.
+    #[must_use]
+    pub fn with_cmp(queue: Q, cmp: C) -> Self {
+        assert!(queue.is_empty(), "the queue under test starts out empty");
+        TestState {
+            queue,
+            naive: NaiveHeap::with_cmp(cmp),
+        }
+    }
+
+    /// Applies a series of operations, checking the queue after each one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first operation after which the queue and the naive heap
+    /// disagree.
+    pub fn apply_ops(&mut self, ops: Vec<Op<T>>) -> Result<(), TestCaseError> {
+        for (idx, op) in ops.into_iter().enumerate() {
+            self.apply_op(op).map_err(|err| match err {
+                TestCaseError::Fail(reason) => {
+                    TestCaseError::fail(format!("for operation {idx}, {reason}"))
+                }
+                err => err,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Applies an operation to both the queue and the naive heap, then checks that they still
+    /// agree on their length and greatest item.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue and the naive heap disagree.
+    pub fn apply_op(&mut self, op: Op<T>) -> Result<(), TestCaseError> {
+        match op {
+            Op::Push(item) => {
+                self.queue.push(item.clone());
+                self.naive.push(item);
+            }
+            Op::Pop => {
+                let queue_item = self.queue.pop();
+                let naive_item = self.naive.pop();
+                prop_assert_eq!(
+                    &queue_item,
+                    &naive_item,
+                    "queue item {:?} is the same as naive item {:?}",
+                    queue_item,
+                    naive_item
+                );
+            }
+            Op::Extend(items) => {
+                self.queue.extend(items.iter().cloned());
+                self.naive.extend(items);
+            }
+        }
+
+        let queue_len = self.queue.len();
+        let naive_len = self.naive.len();
+        prop_assert_eq!(
+            queue_len,
+            naive_len,
+            "queue len {} is the same as naive len {}",
+            queue_len,
+            naive_len
+        );
+        prop_assert_eq!(
+            self.queue.is_empty(),
+            self.naive.is_empty(),
+            "is_empty agrees with len"
+        );
+
+        // Peeking at these elements should produce the same result.
+        let queue_peek = self.queue.peek();
+        let naive_peek = self.naive.peek();
+        prop_assert_eq!(
+            queue_peek,
+            naive_peek,
+            "queue peek {:?} is the same as naive peek {:?}",
+            queue_peek,
+            naive_peek
+        );
+        Ok(())
+    }
+
+    /// Consumes the state and checks that the queue and the naive heap sort to the same items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sorted vectors differ.
+    pub fn check_final(self) -> Result<(), TestCaseError> {
+        prop_assert_eq!(
+            self.queue.into_sorted_vec(),
+            self.naive.into_sorted_vec(),
+            "queue and naive sorted vecs match"
+        );
+        Ok(())
+    }
+}
+
+/// Checks a max-heap with respect to the natural order of its items against the naive heap.
+///
+/// `new_queue` creates an empty queue for every test case, and `items` generates the items that are
+/// pushed onto it. See the [module documentation](self) for what is checked.
+///
+/// # Panics
+///
+/// Panics with the minimal failing input if the queue and the naive heap disagree.
+pub fn check_priority_queue<Q, S>(new_queue: impl Fn() -> Q, items: S)
+where
+    Q: PriorityQueue<S::Value>,
+    S: Strategy + Clone,
+    S::Value: Ord + Clone + fmt::Debug,
+{
+    check_priority_queue_with_cmp(new_queue, MaxComparator, items);
+}
+
+/// Checks a queue that is ordered by the comparator `cmp` against the naive heap.
+///
+/// `new_queue` creates an empty queue for every test case, and `items` generates the items that are
+/// pushed onto it. See the [module documentation](self) for what is checked.
+///
+/// # Panics
+///
+/// Panics with the minimal failing input if the queue and the naive heap disagree.
+pub fn check_priority_queue_with_cmp<Q, C, S>(new_queue: impl Fn() -> Q, cmp: C, items: S)
+where
+    Q: PriorityQueue<S::Value>,
+    C: Compare<S::Value> + Clone,
+    S: Strategy + Clone,
+    S::Value: Clone + fmt::Debug + PartialEq,
+{
+    // Setting a lower bound for vectors is important because that's how far down proptest will
+    // shrink them to.
+    let strategy = (vec(items.clone(), 0..128), vec(Op::strategy(items), 0..128));
+    let result = TestRunner::default().run(&strategy, |(initial, ops)| {
+        let mut state = TestState::with_cmp(new_queue(), cmp.clone());
+        state.apply_op(Op::Extend(initial))?;
+        state.apply_ops(ops)?;
+        state.check_final()
+    });
+    if let Err(err) = result {
+        panic!("{err}");
+    }
+}
@@ -1,162 +1,600 @@
-use crate::binary_heap::BinaryHeap;
+#[cfg(feature = "allocator-api2")]
+mod allocator;
+mod array_heap;
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "std")]
+mod delay_queue;
+mod indexed_heap;
+#[cfg(feature = "std")]
+mod lazy_delete_heap;
+mod min_max_heap;
+#[cfg(feature = "std")]
+mod multi_queue;
+mod pairing_heap;
+mod panic_safety;
+#[cfg(feature = "std")]
+mod priority_queue;
+mod queue;
+mod slice;
+mod stable_heap;
+mod storage;
+#[cfg(feature = "std")]
+mod sync_heap;
+mod top_k;
+
+use crate::binary_heap::storage::HeapStorage;
+use crate::testing::NaiveHeap;
+use crate::{BinaryHeap, DaryHeap, MaxComparator, PeekMut};
 use proptest::collection::vec;
 use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::strategy::Union;
 use proptest_derive::Arbitrary;
+use proptest_state_machine::{prop_state_machine, ReferenceStateMachine, StateMachineTest};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-/// This is a really simple "naive" heap that is trivially correct by inspection.
+/// To test these two data structures, we're going to first define the notion of an operation. This
+/// can be as simple or as complex as we like.
 ///
-/// The time complexity of the `push` operation is `O(n log n)` which makes this infeasible to use
-/// in production, but it will be perfect for our tests.
+/// There is an operation for every public method that mutates a heap. Some of them only exist for
+/// heaps backed by a `Vec`; see [`TestStorage`]. Which operations are generated, and with which
+/// parameters, depends on the state of the heap; see [`HeapModel`].
 #[derive(Clone, Debug)]
-struct NaiveHeap<T> {
-    // The invariant here is that the data is always in sorted order.
-    data: Vec<T>,
+enum Op {
+    /// This is the push operation.
+    Push {
+        /// The value that we're going to push.
+        item: usize,
+    },
+    /// This is the pop operation.
+    Pop,
+    /// A push immediately followed by a pop, fused into a single operation.
+    PushPop {
+        item: usize,
+    },
+    /// A pop immediately followed by a push, fused into a single operation.
+    Replace {
+        item: usize,
+    },
+    /// Overwrites the greatest item through `peek_mut`, which sifts it down when the `PeekMut` is
+    /// dropped.
+    PeekMut {
+        item: usize,
+    },
+    /// Pops the greatest item through `PeekMut::pop`.
+    PeekMutPop,
+    /// Extends the heap with a few items, either by value or by reference.
+    Extend {
+        items: Vec<usize>,
+        by_ref: bool,
+    },
+    /// Moves the items of another heap into this one. The number of items decides whether
+    /// `append` rebuilds the heap or pushes them one by one.
+    Append {
+        items: Vec<usize>,
+    },
+    /// Keeps only the items that aren't divisible by `modulus`. A modulus of 1 removes every item.
+    Retain {
+        modulus: usize,
+    },
+    /// Removes every item in arbitrary order with `drain`.
+    Drain,
+    /// Takes `count` items from `drain_sorted`, then drops it, which removes the rest.
+    DrainSorted {
+        count: usize,
+    },
+    /// Takes `count` items from `into_iter_sorted`, then collects the rest back into a heap.
+    IntoIterSorted {
+        count: usize,
+    },
+    /// Sorts the heap with `into_sorted_vec`, then turns the sorted vector back into a heap.
+    IntoSortedVec,
+    /// Turns the heap into a vector with `into_vec`, then back into a heap.
+    IntoVec,
+    Clear,
+    Reserve {
+        additional: usize,
+    },
+    /// Changes the capacity of a `Vec`, which must not change the contents.
+    Capacity(CapacityOp),
 }
 
-impl<T: Eq + Ord> NaiveHeap<T> {
-    /// Creates a new `NaiveHeap`.
-    pub fn new() -> Self {
-        Self { data: vec![] }
+impl Op {
+    /// Whether the operation only exists for heaps backed by a `Vec`.
+    fn is_vec_only(&self) -> bool {
+        matches!(
+            self,
+            Op::Append { .. }
+                | Op::Drain
+                | Op::IntoIterSorted { .. }
+                | Op::IntoSortedVec
+                | Op::IntoVec
+                | Op::Capacity(_)
+        )
     }
+}
+
+/// Operations that change the capacity of a heap backed by a `Vec`.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum CapacityOp {
+    ReserveExact(#[proptest(strategy = "0usize..64")] usize),
+    TryReserve(#[proptest(strategy = "0usize..64")] usize),
+    TryReserveExact(#[proptest(strategy = "0usize..64")] usize),
+    ShrinkTo(#[proptest(strategy = "0usize..64")] usize),
+    ShrinkToFit,
+}
+
+/// What an operation returned. Applying the same operation to the heap and to the naive heap
+/// should produce the same outcome.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// The operation doesn't return anything.
+    Unit,
+    /// The operation returned at most one item, like `pop`.
+    Item(Option<usize>),
+    /// The operation returned several items, in the order noted by the operation.
+    Items(Vec<usize>),
+}
 
-    /// Pushes an item onto the heap.
-    pub fn push(&mut self, item: T) {
-        // Push the item to the end.
-        self.data.push(item);
-        // Sort the vector.
-        self.data.sort();
+/// Applies an operation to the naive heap, and returns what the same operation on a real heap
+/// should return. Where a heap returns items in arbitrary order, as `drain` and `into_vec` do, the
+/// outcome has them sorted.
+fn apply_naive(naive: &mut NaiveHeap<usize>, op: &Op) -> Outcome {
+    match *op {
+        Op::Push { item } => {
+            naive.push(item);
+            Outcome::Unit
+        }
+        Op::Pop | Op::PeekMutPop => Outcome::Item(naive.pop()),
+        Op::PushPop { item } => Outcome::Item(Some(naive.push_pop(item))),
+        Op::Replace { item } => Outcome::Item(naive.replace(item)),
+        Op::PeekMut { item } => {
+            naive.replace_top(item);
+            Outcome::Unit
+        }
+        Op::Extend { ref items, .. } | Op::Append { ref items } => {
+            naive.extend(items.iter().copied());
+            Outcome::Unit
+        }
+        Op::Retain { modulus } => {
+            naive.retain(|item| item % modulus != 0);
+            Outcome::Unit
+        }
+        Op::Drain => Outcome::Items(naive.drain()),
+        Op::DrainSorted { count } => {
+            Outcome::Items(naive.drain().into_iter().rev().take(count).collect())
+        }
+        Op::IntoIterSorted { count } => {
+            Outcome::Items((0..count).map_while(|_| naive.pop()).collect())
+        }
+        Op::IntoSortedVec | Op::IntoVec => Outcome::Items(naive.as_slice().to_vec()),
+        Op::Clear => {
+            naive.drain();
+            Outcome::Unit
+        }
+        Op::Reserve { .. } | Op::Capacity(_) => Outcome::Unit,
+    }
+}
+
+/// The reference state machine. Its state is simply the naive heap, which is enough to decide
+/// which operations make sense next.
+///
+/// Unlike drawing the operations as an independent vector up front, this lets the strategies
+/// depend on the state: items that are already in the heap, and especially the greatest one, are
+/// pushed again to exercise ties, and the number of items to take from `drain_sorted` is bounded by
+/// the length of the heap. When a test fails, proptest shrinks it by removing individual
+/// operations, and the preconditions make sure that the operations left over still make sense.
+///
+/// `VEC` says whether to generate the operations that only exist for heaps backed by a `Vec`.
+struct HeapModel<const VEC: bool>;
+
+impl<const VEC: bool> HeapModel<VEC> {
+    /// Returns a strategy for items to add to a heap in the given state.
+    fn items(state: &NaiveHeap<usize>) -> BoxedStrategy<usize> {
+        match state.peek() {
+            Some(&max) => prop_oneof![
+                2 => any::<usize>(),
+                1 => select(state.as_slice().to_vec()),
+                1 => Just(max),
+            ]
+            .boxed(),
+            None => any::<usize>().boxed(),
+        }
+    }
+}
+
+impl<const VEC: bool> ReferenceStateMachine for HeapModel<VEC> {
+    type State = NaiveHeap<usize>;
+    type Transition = Op;
+
+    /// The initial state is a vector of 0 to 128 integers. Setting a lower bound for vectors and
+    /// other collections is important because that's how far down proptest will shrink them to.
+    fn init_state() -> BoxedStrategy<Self::State> {
+        vec(any::<usize>(), 0..128)
+            .prop_map(|items| {
+                let mut naive = NaiveHeap::new();
+                naive.extend(items);
+                naive
+            })
+            .boxed()
     }
 
-    /// Removes the greatest item from the heap and returns it, or `None` if it is empty.
-    pub fn pop(&mut self) -> Option<T> {
-        // Data is always in sorted order so last element is greatest.
-        self.data.pop()
+    /// Each operation gets a weight, and proptest picks one with a probability proportional to
+    /// its weight. Pushes and pops come several times as often as the other operations, so that
+    /// the heap has items to work on.
+    fn transitions(state: &Self::State) -> BoxedStrategy<Self::Transition> {
+        let items = Self::items(state);
+        let len = state.len();
+        let mut ops: Vec<(u32, BoxedStrategy<Op>)> = vec![
+            (4, items.clone().prop_map(|item| Op::Push { item }).boxed()),
+            (4, Just(Op::Pop).boxed()),
+            (
+                1,
+                items.clone().prop_map(|item| Op::PushPop { item }).boxed(),
+            ),
+            (
+                1,
+                items.clone().prop_map(|item| Op::Replace { item }).boxed(),
+            ),
+            (
+                1,
+                (vec(items.clone(), 0..4), any::<bool>())
+                    .prop_map(|(items, by_ref)| Op::Extend { items, by_ref })
+                    .boxed(),
+            ),
+            (
+                1,
+                (1usize..8)
+                    .prop_map(|modulus| Op::Retain { modulus })
+                    .boxed(),
+            ),
+            (
+                1,
+                (0..=len)
+                    .prop_map(|count| Op::DrainSorted { count })
+                    .boxed(),
+            ),
+            (1, Just(Op::Clear).boxed()),
+            (
+                1,
+                (0usize..64)
+                    .prop_map(|additional| Op::Reserve { additional })
+                    .boxed(),
+            ),
+        ];
+        if len > 0 {
+            ops.extend([
+                (
+                    1,
+                    items.clone().prop_map(|item| Op::PeekMut { item }).boxed(),
+                ),
+                (1, Just(Op::PeekMutPop).boxed()),
+            ]);
+        }
+        if VEC {
+            ops.extend([
+                (
+                    1,
+                    vec(items, 0..16)
+                        .prop_map(|items| Op::Append { items })
+                        .boxed(),
+                ),
+                (1, Just(Op::Drain).boxed()),
+                (
+                    1,
+                    (0..=len)
+                        .prop_map(|count| Op::IntoIterSorted { count })
+                        .boxed(),
+                ),
+                (1, Just(Op::IntoSortedVec).boxed()),
+                (1, Just(Op::IntoVec).boxed()),
+                (1, any::<CapacityOp>().prop_map(Op::Capacity).boxed()),
+            ]);
+        }
+        Union::new_weighted(ops).boxed()
     }
 
-    /// Returns the greatest element in the heap.
-    pub fn peek(&self) -> Option<&T> {
-        // Data is always in sorted order so last element is greatest.
-        self.data.last()
+    fn apply(mut state: Self::State, transition: &Self::Transition) -> Self::State {
+        apply_naive(&mut state, transition);
+        state
     }
 
-    /// Consumes the heap and returns a vector in sorted (ascending) order.
-    pub fn into_sorted_vec(self) -> Vec<T> {
-        // self.data is already sorted so it's as simple as returning it
-        self.data
+    /// Shrinking can remove the operations that filled the heap, so the operations that were
+    /// generated for a non-empty heap, or for a heap of a certain length, check that it still is.
+    fn preconditions(state: &Self::State, transition: &Self::Transition) -> bool {
+        match *transition {
+            Op::PeekMut { .. } | Op::PeekMutPop => !state.is_empty(),
+            Op::DrainSorted { count } | Op::IntoIterSorted { count } => count <= state.len(),
+            ref op => VEC || !op.is_vec_only(),
+        }
     }
 }
 
-impl<A: Eq + Ord> Extend<A> for NaiveHeap<A> {
-    fn extend<T: IntoIterator<Item = A>>(&mut self, iter: T) {
-        self.data.extend(iter);
-        self.data.sort();
+/// The parts of the model test that depend on how the heap stores its elements.
+///
+/// Some methods, like `append` and `into_sorted_vec`, only exist for heaps backed by a `Vec`.
+/// [`HeapModel`] only generates those operations for a `Vec`.
+trait TestStorage: HeapStorage<usize> + Sized {
+    /// Applies an operation that only exists for heaps backed by a `Vec`.
+    fn apply_vec_op<const D: usize>(
+        _heap: &mut DaryHeap<usize, D, MaxComparator, Self>,
+        op: &Op,
+    ) -> Outcome {
+        unreachable!("{op:?} is only generated for heaps backed by a Vec")
     }
 }
 
-/// To test these two data structures, we're going to first define the notion of an operation. This
-/// can be as simple or as complex as we like.
-#[derive(Clone, Copy, Debug, Arbitrary)]
-enum Op {
-    /// By default proptest picks enum variants uniformly randomly, but we can also assign separate
-    /// weights for each variant. In this case, let's say that we do pushes 1/3rd of the time and
-    /// pops 2/3rd.
-    #[proptest(weight = 1)]
-    Push {
-        /// The value that we're going to push.
-        ///
-        /// Core types like usize have a default generation strategy provided by their Arbitrary
-        /// implementation. For integers, the default strategy is simply to uniformly randomly pick
-        /// any possible integer.
-        ///
-        /// We can also specify a strategy explicitly. Here, we can specify a range of integers to
-        /// match against.
-        #[proptest(strategy = "usize::MIN ..= usize::MAX")]
-        item: usize,
-    },
-    /// This is the pop operation.
-    #[proptest(weight = 2)]
-    Pop,
+impl TestStorage for Vec<usize> {
+    fn apply_vec_op<const D: usize>(heap: &mut DaryHeap<usize, D>, op: &Op) -> Outcome {
+        match *op {
+            Op::Append { ref items } => {
+                let mut other = DaryHeap::from(items.clone());
+                heap.append(&mut other);
+                assert!(other.is_empty(), "append empties the other heap");
+                Outcome::Unit
+            }
+            Op::Drain => {
+                let mut drained: Vec<_> = heap.drain().collect();
+                drained.sort();
+                Outcome::Items(drained)
+            }
+            Op::IntoIterSorted { count } => {
+                let mut iter = std::mem::take(heap).into_iter_sorted();
+                let items = iter.by_ref().take(count).collect();
+                *heap = iter.collect();
+                Outcome::Items(items)
+            }
+            Op::IntoSortedVec => {
+                let sorted = std::mem::take(heap).into_sorted_vec();
+                *heap = DaryHeap::from(sorted.clone());
+                Outcome::Items(sorted)
+            }
+            Op::IntoVec => {
+                let vec = std::mem::take(heap).into_vec();
+                let mut sorted = vec.clone();
+                sorted.sort();
+                *heap = DaryHeap::from(vec);
+                Outcome::Items(sorted)
+            }
+            Op::Capacity(op) => {
+                let len = heap.len();
+                match op {
+                    CapacityOp::ReserveExact(additional) => {
+                        heap.reserve_exact(additional);
+                        assert!(heap.capacity() >= len + additional);
+                    }
+                    CapacityOp::TryReserve(additional) => {
+                        heap.try_reserve(additional)
+                            .expect("small reservations succeed");
+                        assert!(heap.capacity() >= len + additional);
+                    }
+                    CapacityOp::TryReserveExact(additional) => {
+                        heap.try_reserve_exact(additional)
+                            .expect("small reservations succeed");
+                        assert!(heap.capacity() >= len + additional);
+                    }
+                    CapacityOp::ShrinkTo(min_capacity) => {
+                        let capacity = heap.capacity();
+                        heap.shrink_to(min_capacity);
+                        assert!(heap.capacity() >= len.max(min_capacity.min(capacity)));
+                    }
+                    CapacityOp::ShrinkToFit => {
+                        heap.shrink_to_fit();
+                        assert!(heap.capacity() >= len);
+                    }
+                }
+                Outcome::Unit
+            }
+            ref op => unreachable!("{op:?} is handled for every storage"),
+        }
+    }
 }
 
-/// This struct defines the test state. It contains the data structure under test (the `BinaryHeap`)
-/// and the naive data structure (the `NaiveBinaryHeap`) that acts as a baseline.
-#[derive(Clone, Debug)]
-struct TestState {
-    heap: BinaryHeap<usize>,
+impl TestStorage for &mut Vec<usize> {}
+
+/// This struct defines the test state. It contains the data structure under test (the `DaryHeap`,
+/// which is a `BinaryHeap` when `D` is 2) and the naive data structure (the `NaiveHeap`) that
+/// acts as a baseline. The heap keeps its elements in the storage `S`, which is a `Vec` unless a
+/// test says otherwise.
+///
+/// The naive heap here mirrors the state of the [`HeapModel`]. Keeping a copy lets each operation
+/// compare what the heap returns with what the naive heap returns.
+struct TestState<const D: usize, S = Vec<usize>> {
+    heap: DaryHeap<usize, D, MaxComparator, S>,
     naive: NaiveHeap<usize>,
 }
 
-impl TestState {
+impl<const D: usize> TestState<D> {
     /// Creates a new `TestState` with the same contents across the test heap and the naive heap.
-    fn new(initial: Vec<usize>) -> Self {
-        let mut heap = BinaryHeap::new();
-        heap.extend(&initial);
-        let mut naive = NaiveHeap::new();
-        naive.extend(initial);
+    fn new(initial: &NaiveHeap<usize>) -> Self {
+        Self::from_storage(Vec::new(), initial)
+    }
+
+    fn assert_final(self) {
+        // Check that the final sorted vec is the same at the end.
+        assert_eq!(
+            self.heap.into_sorted_vec(),
+            self.naive.into_sorted_vec(),
+            "heap and naive sorted vecs match"
+        );
+    }
+}
+
+impl<const D: usize, S: TestStorage> TestState<D, S> {
+    /// Creates a new `TestState` whose heap keeps its elements in `storage`, which must be empty.
+    fn from_storage(storage: S, initial: &NaiveHeap<usize>) -> Self {
+        let mut heap = DaryHeap::from_storage(storage, MaxComparator);
+        heap.extend(initial.as_slice());
 
-        Self { heap, naive }
+        Self {
+            heap,
+            naive: initial.clone(),
+        }
     }
 
-    /// Apply a series of operations and perform assertions along the way.
-    fn apply_ops_and_assert(&mut self, ops: Vec<Op>) {
-        for (idx, op) in ops.into_iter().enumerate() {
-            self.apply_op_and_assert(idx, op);
+    /// Apply a series of operations generated by [`HeapModel`] and perform assertions along the
+    /// way.
+    ///
+    /// `seen_counter` comes from the model's strategy, which uses it to drop the operations that
+    /// never ran when it starts shrinking a failing test. `prop_state_machine!` takes care of this
+    /// for the tests that use it.
+    fn apply_ops_and_assert(&mut self, ops: Vec<Op>, seen_counter: Option<&AtomicUsize>) {
+        for op in ops {
+            if let Some(seen_counter) = seen_counter {
+                seen_counter.fetch_add(1, Ordering::SeqCst);
+            }
+            self.apply_op_and_assert(op);
         }
     }
 
     /// Apply an operation and perform an assert.
-    fn apply_op_and_assert(&mut self, idx: usize, op: Op) {
-        match op {
+    fn apply_op_and_assert(&mut self, op: Op) {
+        let expected = apply_naive(&mut self.naive, &op);
+        let outcome = match op {
             Op::Push { item } => {
                 self.heap.push(item);
-                self.naive.push(item);
+                Outcome::Unit
+            }
+            Op::Pop => Outcome::Item(self.heap.pop()),
+            Op::PushPop { item } => Outcome::Item(Some(self.heap.push_pop(item))),
+            Op::Replace { item } => Outcome::Item(self.heap.replace(item)),
+            Op::PeekMut { item } => {
+                if let Some(mut top) = self.heap.peek_mut() {
+                    *top = item;
+                }
+                Outcome::Unit
+            }
+            Op::PeekMutPop => Outcome::Item(self.heap.peek_mut().map(PeekMut::pop)),
+            Op::Extend { ref items, by_ref } => {
+                if by_ref {
+                    self.heap.extend(items);
+                } else {
+                    self.heap.extend(items.iter().copied());
+                }
+                Outcome::Unit
+            }
+            Op::Retain { modulus } => {
+                self.heap.retain(|item| item % modulus != 0);
+                Outcome::Unit
             }
-            Op::Pop => {
-                let heap_item = self.heap.pop();
-                let naive_item = self.naive.pop();
-                assert_eq!(
-                    heap_item, naive_item,
-                    "for operation {idx}, heap item {heap_item:?} is the same as naive item {naive_item:?}"
+            Op::DrainSorted { count } => {
+                let items = self.heap.drain_sorted().take(count).collect();
+                assert!(
+                    self.heap.is_empty(),
+                    "for {op:?}, dropping drain_sorted removes the rest"
                 );
+                Outcome::Items(items)
             }
+            Op::Clear => {
+                self.heap.clear();
+                Outcome::Unit
+            }
+            Op::Reserve { additional } => {
+                // Storages with a fixed capacity ignore this.
+                self.heap.reserve(additional);
+                Outcome::Unit
+            }
+            ref op => S::apply_vec_op(&mut self.heap, op),
+        };
+        // Check the heap property first, so that a bug shows up at the operation that broke the
+        // heap rather than at a later pop that returns the wrong item.
+        if let Err(violation) = self.heap.check_invariants() {
+            panic!("for {op:?}, {violation}");
         }
+        assert_eq!(
+            outcome, expected,
+            "for {op:?}, heap outcome {outcome:?} is the same as naive outcome {expected:?}"
+        );
+        self.assert_matches(&self.naive);
+    }
+
+    /// Checks that the heap has the same length and greatest item as the naive heap `naive`.
+    fn assert_matches(&self, naive: &NaiveHeap<usize>) {
+        let heap_len = self.heap.len();
+        let naive_len = naive.len();
+        assert_eq!(
+            heap_len, naive_len,
+            "heap len {heap_len} is the same as naive len {naive_len}"
+        );
 
         // Peeking at these elements should produce the same result.
         let heap_peek = self.heap.peek();
-        let naive_peek = self.naive.peek();
+        let naive_peek = naive.peek();
         assert_eq!(
             heap_peek, naive_peek,
-            "for operation {idx}, heap peek {heap_peek:?} is the same as naive peek {naive_peek:?}"
+            "heap peek {heap_peek:?} is the same as naive peek {naive_peek:?}"
         );
     }
+}
 
-    fn assert_final(self) {
-        // Check that the final sorted vec is the same at the end.
-        assert_eq!(
-            self.heap.into_sorted_vec(),
-            self.naive.into_sorted_vec(),
-            "heap and naive sorted vecs match"
-        );
+/// Ties the [`HeapModel`] to a `DaryHeap` backed by a `Vec`, which supports every operation.
+struct HeapTest<const D: usize>;
+
+impl<const D: usize> StateMachineTest for HeapTest<D> {
+    type SystemUnderTest = TestState<D>;
+    type Reference = HeapModel<true>;
+
+    fn init_test(ref_state: &NaiveHeap<usize>) -> Self::SystemUnderTest {
+        TestState::new(ref_state)
+    }
+
+    fn apply(mut state: TestState<D>, _ref_state: &NaiveHeap<usize>, op: Op) -> TestState<D> {
+        state.apply_op_and_assert(op);
+        state
+    }
+
+    fn check_invariants(state: &TestState<D>, ref_state: &NaiveHeap<usize>) {
+        state.assert_matches(ref_state);
+    }
+
+    fn teardown(state: TestState<D>, _ref_state: NaiveHeap<usize>) {
+        state.assert_final();
     }
 }
 
-proptest! {
+prop_state_machine! {
     /// This is the test.
     ///
-    /// The test takes in two proptest strategies as arguments:
-    /// * the initial state, which is a vector of 0 to 128 integers. The size of the vector 0..128
-    ///   is uniformly randomly picked, and the integers are then uniformly randomly picked
-    ///   afterwards.
-    /// * a list of operations, which is a vector of 0 to 128 operations. The any::<Op>() call uses
-    ///   the default strategy defined by the `Arbitrary` implementation here.
-    ///
-    /// Setting a lower bound for vectors and other collections is important because that's how far
-    /// down proptest will shrink them to. Typical lower bounds are 0 and 1.
+    /// The test runs a sequence of up to 128 operations, drawn from the strategies in [`HeapModel`],
+    /// against a `BinaryHeap` that starts out with the items of the model's initial state.
     #[test]
-    fn test_compare_heaps(initial in vec(any::<usize>(), 0..128), ops in vec(any::<Op>(), 0..128)) {
-        let mut state = TestState::new(initial);
-        state.apply_ops_and_assert(ops);
+    fn test_compare_heaps(sequential 1..128 => HeapTest<2>);
 
-        state.assert_final();
+    /// The same test, run against heaps of other arities. The arity only changes the index math,
+    /// so the same model works for all of them.
+    #[test]
+    fn test_compare_3ary_heaps(sequential 1..128 => HeapTest<3>);
+
+    #[test]
+    fn test_compare_4ary_heaps(sequential 1..128 => HeapTest<4>);
+
+    #[test]
+    fn test_compare_8ary_heaps(sequential 1..128 => HeapTest<8>);
+}
+
+proptest! {
+    /// Heaps with comparators other than the default should pop elements in the order defined by
+    /// their comparator.
+    #[test]
+    fn test_comparators(items in vec(any::<i32>(), 0..128)) {
+        let mut ascending = items.clone();
+        ascending.sort();
+
+        let mut min_heap = BinaryHeap::new_min();
+        min_heap.extend(items.iter().copied());
+        let popped: Vec<_> = std::iter::from_fn(|| min_heap.pop()).collect();
+        prop_assert_eq!(&popped, &ascending, "min-heap pops in ascending order");
+
+        let mut by_heap = BinaryHeap::new_by(|a: &i32, b: &i32| a.cmp(b));
+        by_heap.extend(&items);
+        prop_assert_eq!(&by_heap.into_sorted_vec(), &ascending, "closure heap sorts ascending");
+
+        let mut key_heap = BinaryHeap::new_by_key(|a: &i32| a.unsigned_abs());
+        key_heap.extend(&items);
+        let popped: Vec<_> = std::iter::from_fn(|| key_heap.pop()).map(i32::unsigned_abs).collect();
+        let mut descending_keys: Vec<_> = items.iter().map(|a| a.unsigned_abs()).collect();
+        descending_keys.sort_by(|a, b| b.cmp(a));
+        prop_assert_eq!(popped, descending_keys, "key heap pops in descending key order");
     }
 }
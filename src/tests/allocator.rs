@@ -0,0 +1,95 @@
+use super::{HeapModel, TestState, TestStorage};
+use crate::{BinaryHeap, MaxComparator, MinComparator};
+use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+use allocator_api2::vec::Vec as AllocVec;
+use proptest::prelude::*;
+use proptest_state_machine::ReferenceStateMachine;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// An allocator that counts the bytes it has handed out, and fails once more than `limit` bytes
+/// would be live at once.
+#[derive(Clone, Debug)]
+struct LimitedAllocator {
+    live: Rc<Cell<usize>>,
+    limit: usize,
+}
+
+impl LimitedAllocator {
+    fn new(limit: usize) -> Self {
+        Self {
+            live: Rc::new(Cell::new(0)),
+            limit,
+        }
+    }
+}
+
+unsafe impl Allocator for LimitedAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let live = self.live.get() + layout.size();
+        if live > self.limit {
+            return Err(AllocError);
+        }
+        let ptr = Global.allocate(layout)?;
+        self.live.set(live);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - layout.size());
+        unsafe { Global.deallocate(ptr, layout) };
+    }
+}
+
+impl<A: Allocator> TestStorage for AllocVec<usize, A> {}
+
+proptest! {
+    /// A heap that allocates with a custom allocator should behave exactly like one backed by a
+    /// standard `Vec`, and hand all of its memory back to the allocator when dropped.
+    #[test]
+    fn test_custom_allocator((initial, ops, seen_counter) in HeapModel::<false>::sequential_strategy(1..128)) {
+        let alloc = LimitedAllocator::new(usize::MAX);
+        let mut state = TestState::<2, _>::from_storage(AllocVec::new_in(alloc.clone()), &initial);
+        state.apply_ops_and_assert(ops, seen_counter.as_deref());
+        prop_assert!(alloc.live.get() >= state.heap.capacity() * size_of::<usize>());
+
+        let mut expected = state.naive.into_sorted_vec();
+        expected.reverse();
+        let popped: Vec<_> = state.heap.drain_sorted().collect();
+        prop_assert_eq!(popped, expected, "heap and naive drain in the same order");
+
+        drop(state.heap);
+        prop_assert_eq!(alloc.live.get(), 0, "all memory is returned to the allocator");
+    }
+}
+
+#[test]
+fn test_try_reserve_in() {
+    let alloc = LimitedAllocator::new(64);
+    let mut heap = BinaryHeap::new_in(alloc.clone());
+    heap.try_reserve_exact(8).expect("8 u64s fit in 64 bytes");
+    assert_eq!(heap.capacity(), 8);
+    assert_eq!(alloc.live.get(), 64);
+    heap.extend([3u64, 1, 4, 1, 5, 9, 2, 6]);
+
+    // Growing needs a new allocation on top of the live one, which the allocator refuses.
+    assert!(heap.try_reserve(1).is_err());
+    assert!(heap.try_reserve_exact(1).is_err());
+    assert_eq!(heap.len(), 8, "a failed reservation leaves the heap alone");
+    assert_eq!(heap.pop(), Some(9));
+    heap.try_reserve(1)
+        .expect("there is spare capacity after a pop");
+
+    drop(heap);
+    assert_eq!(alloc.live.get(), 0);
+
+    let heap =
+        BinaryHeap::from_storage(AllocVec::with_capacity_in(4, alloc.clone()), MinComparator);
+    assert_eq!(heap.allocator().live.get(), 4 * size_of::<u64>());
+    drop::<BinaryHeap<u64, _, _>>(heap);
+    assert_eq!(alloc.live.get(), 0);
+
+    let heap: BinaryHeap<u64, MaxComparator, _> = BinaryHeap::with_capacity_in(0, alloc.clone());
+    assert_eq!(heap.capacity(), 0);
+}
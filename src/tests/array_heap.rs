@@ -0,0 +1,108 @@
+use crate::array_heap::{ArrayHeap, PeekMut};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_derive::Arbitrary;
+use std::rc::Rc;
+
+/// The capacity of the heap under test. This is small so that the heap is frequently full.
+const N: usize = 8;
+
+/// Operations on an `ArrayHeap`.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 3)]
+    TryPush {
+        item: u16,
+    },
+    Pop,
+    SetTop {
+        item: u16,
+    },
+    PeekMutPop,
+    /// Drains `count` items in heap order, then drops the drain.
+    DrainSorted {
+        #[proptest(strategy = "0usize..4")]
+        count: usize,
+    },
+}
+
+proptest! {
+    #[test]
+    fn test_array_heap(ops in vec(any::<Op>(), 0..256)) {
+        let mut heap = ArrayHeap::<u16, N>::new();
+        // The model is kept in ascending order, so the greatest item is last.
+        let mut model: Vec<u16> = vec![];
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::TryPush { item } => {
+                    let result = heap.try_push(item);
+                    if model.len() < N {
+                        prop_assert_eq!(result, Ok(()), "for operation {}", idx);
+                        model.push(item);
+                        model.sort();
+                    } else {
+                        prop_assert_eq!(result, Err(item), "for operation {}", idx);
+                    }
+                }
+                Op::Pop => {
+                    prop_assert_eq!(heap.pop(), model.pop(), "for operation {}", idx);
+                }
+                Op::SetTop { item } => {
+                    if let Some(mut top) = heap.peek_mut() {
+                        *top = item;
+                        model.pop();
+                        model.push(item);
+                        model.sort();
+                    }
+                }
+                Op::PeekMutPop => {
+                    prop_assert_eq!(heap.peek_mut().map(PeekMut::pop), model.pop(), "for operation {}", idx);
+                }
+                Op::DrainSorted { count } => {
+                    let mut drain = heap.drain_sorted();
+                    for _ in 0..count {
+                        prop_assert_eq!(drain.next(), model.pop(), "for operation {}", idx);
+                    }
+                    drop(drain);
+                    model.clear();
+                }
+            }
+
+            prop_assert_eq!(heap.len(), model.len(), "for operation {}", idx);
+            prop_assert_eq!(heap.peek(), model.last(), "for operation {}", idx);
+        }
+
+        match heap.into_sorted_array() {
+            Ok(sorted) => prop_assert_eq!(&sorted[..], &model[..], "sorted arrays match"),
+            Err(heap) => {
+                prop_assert!(model.len() < N, "only a heap that isn't full is returned");
+                let drained: Vec<_> = heap.clone().drain_sorted().collect();
+                prop_assert_eq!(drained.into_iter().rev().collect::<Vec<_>>(), model);
+            }
+        }
+    }
+
+    /// Every element that goes into the heap should be dropped exactly once, whichever way it
+    /// leaves.
+    #[test]
+    fn test_array_heap_drops(items in vec(any::<u16>(), 0..=N), pops in 0..=N, sorted: bool) {
+        let counter = Rc::new(());
+        {
+            let mut heap = ArrayHeap::<(u16, Rc<()>), N>::new();
+            for &item in &items {
+                heap.try_push((item, Rc::clone(&counter))).unwrap();
+            }
+            for _ in 0..pops {
+                drop(heap.pop());
+            }
+            let clone = heap.clone();
+            prop_assert_eq!(Rc::strong_count(&counter), 1 + 2 * heap.len());
+            drop(clone);
+            if sorted {
+                drop(heap.into_sorted_array());
+            }
+        }
+        prop_assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::channel::{self, TryRecvError, TrySendError};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// A minimal single-threaded executor that polls a fixed set of tasks until they stop making
+/// progress.
+///
+/// Tasks are only polled after they have been woken, so a lost wakeup shows up as a task that
+/// never finishes.
+struct Executor<'a> {
+    tasks: Vec<Option<Pin<Box<dyn Future<Output = ()> + 'a>>>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+struct TaskWaker {
+    index: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.index);
+    }
+}
+
+impl<'a> Executor<'a> {
+    fn new() -> Self {
+        Self {
+            tasks: vec![],
+            ready: Arc::default(),
+        }
+    }
+
+    fn spawn(&mut self, task: impl Future<Output = ()> + 'a) {
+        self.ready.lock().unwrap().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(task)));
+    }
+
+    /// Polls woken tasks in the order they were woken, until none are left. Returns the number of
+    /// tasks that didn't finish.
+    fn run(&mut self) -> usize {
+        loop {
+            let Some(index) = self.ready.lock().unwrap().pop_front() else {
+                break;
+            };
+            let Some(task) = &mut self.tasks[index] else {
+                continue;
+            };
+            let waker = Waker::from(Arc::new(TaskWaker {
+                index,
+                ready: self.ready.clone(),
+            }));
+            if task
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_ready()
+            {
+                self.tasks[index] = None;
+            }
+        }
+        self.tasks.iter().filter(|task| task.is_some()).count()
+    }
+}
+
+/// Runs a future to completion on the current thread, parking it while the future is pending.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// A waker that counts how many times it was woken.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+proptest! {
+    /// Several producers and consumers share a bounded channel. Every message is received
+    /// exactly once, the channel never holds more than its capacity, and once the producers are
+    /// done every consumer sees the channel close.
+    #[test]
+    fn test_channel_mpmc(
+        capacity in 1usize..8,
+        batches in vec(vec(any::<u16>(), 0..64), 1..5),
+        consumers in 1usize..4,
+    ) {
+        let (tx, rx) = channel::bounded(capacity);
+        let received = RefCell::new(Vec::new());
+        let max_len = Cell::new(0);
+
+        let mut executor = Executor::new();
+        for batch in &batches {
+            let tx = tx.clone();
+            let max_len = &max_len;
+            executor.spawn(async move {
+                for &item in batch {
+                    tx.send(item).await.unwrap();
+                    max_len.set(max_len.get().max(tx.len()));
+                }
+            });
+        }
+        drop(tx);
+        for _ in 0..consumers {
+            let rx = rx.clone();
+            let received = &received;
+            executor.spawn(async move {
+                while let Some(item) = rx.recv().await {
+                    received.borrow_mut().push(item);
+                }
+            });
+        }
+
+        prop_assert_eq!(executor.run(), 0, "every task finished");
+        drop(executor);
+        prop_assert!(max_len.get() <= capacity, "the channel stayed within its capacity");
+        prop_assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+        let mut received = received.into_inner();
+        received.sort_unstable();
+        let mut expected: Vec<_> = batches.into_iter().flatten().collect();
+        expected.sort_unstable();
+        prop_assert_eq!(received, expected, "every message was received exactly once");
+    }
+
+    /// Messages that are already pending are received greatest first.
+    #[test]
+    fn test_channel_priority_order(items in vec(any::<i32>(), 0..64)) {
+        let (tx, rx) = channel::unbounded();
+        for &item in &items {
+            tx.try_send(item).unwrap();
+        }
+        drop(tx);
+
+        let received = block_on(async {
+            let mut received = vec![];
+            while let Some(item) = rx.recv().await {
+                received.push(item);
+            }
+            received
+        });
+        let mut expected = items;
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        prop_assert_eq!(received, expected);
+    }
+}
+
+#[test]
+fn test_channel_threads() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const ITEMS_PER_PRODUCER: usize = 2_000;
+
+    let (tx, rx) = channel::bounded(16);
+    let mut received: Vec<_> = thread::scope(|s| {
+        for producer in 0..PRODUCERS {
+            let tx = tx.clone();
+            s.spawn(move || {
+                block_on(async {
+                    for i in 0..ITEMS_PER_PRODUCER {
+                        tx.send(producer * ITEMS_PER_PRODUCER + i).await.unwrap();
+                    }
+                });
+            });
+        }
+        drop(tx);
+
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let rx = rx.clone();
+                s.spawn(move || {
+                    block_on(async {
+                        let mut received = vec![];
+                        while let Some(item) = rx.recv().await {
+                            received.push(item);
+                        }
+                        received
+                    })
+                })
+            })
+            .collect();
+        consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect()
+    });
+
+    received.sort_unstable();
+    let expected: Vec<_> = (0..PRODUCERS * ITEMS_PER_PRODUCER).collect();
+    assert_eq!(
+        received, expected,
+        "every message was received exactly once"
+    );
+}
+
+#[test]
+fn test_channel_backpressure() {
+    let (tx, rx) = channel::bounded(1);
+    tx.try_send(1).unwrap();
+
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let mut send = pin!(tx.send(2));
+    assert!(
+        send.as_mut().poll(&mut cx).is_pending(),
+        "the channel is full"
+    );
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(
+        counter.0.load(Ordering::SeqCst),
+        1,
+        "receiving woke the sender"
+    );
+    assert_eq!(send.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+
+    // A sender waiting for room fails once the receivers are gone.
+    let mut send = pin!(tx.send(3));
+    assert!(send.as_mut().poll(&mut cx).is_pending());
+    drop(rx);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+    let err = match send.as_mut().poll(&mut cx) {
+        Poll::Ready(Err(err)) => err,
+        other => panic!("expected the send to fail, got {other:?}"),
+    };
+    assert_eq!(err.into_inner(), 3);
+    assert!(tx.is_closed());
+    assert_eq!(tx.try_send(4), Err(TrySendError::Closed(4)));
+}
+
+#[test]
+fn test_channel_cancelled_recv_passes_wakeup_on() {
+    let (tx, rx) = channel::unbounded();
+
+    let first = Arc::new(CountingWaker::default());
+    let second = Arc::new(CountingWaker::default());
+    let mut first_recv = Box::pin(rx.recv());
+    let mut second_recv = pin!(rx.recv());
+    assert!(first_recv
+        .as_mut()
+        .poll(&mut Context::from_waker(&Waker::from(first.clone())))
+        .is_pending());
+    assert!(second_recv
+        .as_mut()
+        .poll(&mut Context::from_waker(&Waker::from(second.clone())))
+        .is_pending());
+
+    tx.try_send(1).unwrap();
+    assert_eq!(
+        first.0.load(Ordering::SeqCst),
+        1,
+        "the first waiter is woken"
+    );
+    assert_eq!(second.0.load(Ordering::SeqCst), 0);
+
+    // If the woken future is dropped without receiving the message, the next one is woken.
+    drop(first_recv);
+    assert_eq!(second.0.load(Ordering::SeqCst), 1);
+    let waker = Waker::from(second);
+    assert_eq!(
+        second_recv.as_mut().poll(&mut Context::from_waker(&waker)),
+        Poll::Ready(Some(1))
+    );
+
+    drop(tx);
+    assert_eq!(block_on(rx.recv()), None);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+fn test_channel_huge_capacity() {
+    let (tx, rx) = channel::bounded(usize::MAX);
+    assert_eq!(tx.capacity(), Some(usize::MAX));
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    assert_eq!(rx.try_recv(), Ok(2));
+}
+
+/// A waker that uses the channel as soon as it is woken, like an executor that polls the woken
+/// task inline.
+struct ReentrantWaker(channel::Receiver<i32>);
+
+impl Wake for ReentrantWaker {
+    fn wake(self: Arc<Self>) {
+        let _ = self.0.len();
+    }
+}
+
+#[test]
+fn test_channel_wakes_without_lock() {
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        let (tx, rx) = channel::bounded(1);
+        let waker = Waker::from(Arc::new(ReentrantWaker(rx.clone())));
+        let mut cx = Context::from_waker(&waker);
+
+        // Sending wakes a waiting receiver.
+        let mut recv = pin!(rx.recv());
+        assert!(recv.as_mut().poll(&mut cx).is_pending());
+        tx.try_send(1).unwrap();
+        assert_eq!(recv.as_mut().poll(&mut cx), Poll::Ready(Some(1)));
+
+        // Receiving wakes a waiting sender.
+        tx.try_send(2).unwrap();
+        {
+            let mut send = pin!(tx.send(3));
+            assert!(send.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(rx.try_recv(), Ok(2));
+            assert_eq!(send.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+        }
+        assert_eq!(rx.try_recv(), Ok(3));
+
+        // Dropping the last sender wakes every waiting receiver.
+        let mut recv = pin!(rx.recv());
+        assert!(recv.as_mut().poll(&mut cx).is_pending());
+        drop(tx);
+        assert_eq!(recv.as_mut().poll(&mut cx), Poll::Ready(None));
+        done_tx.send(()).unwrap();
+    });
+    done_rx
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .expect("waking a future deadlocked on the channel's lock");
+}
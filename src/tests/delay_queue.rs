@@ -0,0 +1,137 @@
+use crate::delay_queue::{Clock, DelayQueue, Key, ManualClock};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest_derive::Arbitrary;
+use std::time::{Duration, Instant};
+
+/// A model of a `DelayQueue`: a list of items with their deadlines, in insertion (or reset) order.
+///
+/// The next item to expire is found with a linear scan for the earliest deadline. Among equal
+/// deadlines, the scan keeps the first one, which is the one that was inserted or reset first.
+struct NaiveDelayQueue {
+    entries: Vec<(Key, Instant, u32)>,
+}
+
+impl NaiveDelayQueue {
+    fn next(&self) -> Option<usize> {
+        (0..self.entries.len()).min_by_key(|&pos| self.entries[pos].1)
+    }
+
+    fn poll_expired(&mut self, now: Instant) -> Option<u32> {
+        let pos = self.next().filter(|&pos| self.entries[pos].1 <= now)?;
+        Some(self.entries.remove(pos).2)
+    }
+
+    fn position(&self, key: Key) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.0 == key)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    /// Deadlines are drawn from a small range so that there are plenty of ties.
+    #[proptest(weight = 3)]
+    Insert(#[proptest(strategy = "0u64..16")] u64),
+    /// Inserts with a deadline relative to the start of the test, which may be in the past.
+    InsertAt(#[proptest(strategy = "0u64..32")] u64),
+    Reset(Index, #[proptest(strategy = "0u64..16")] u64),
+    Remove(Index),
+    Advance(#[proptest(strategy = "0u64..8")] u64),
+    #[proptest(weight = 2)]
+    PollExpired,
+    WaitNext,
+}
+
+proptest! {
+    /// Items should expire earliest deadline first, with ties broken by insertion order, and only
+    /// once the clock has reached their deadline.
+    #[test]
+    fn test_delay_queue(ops in vec(any::<Op>(), 0..256)) {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut queue = DelayQueue::with_clock(&clock);
+        let mut naive = NaiveDelayQueue { entries: vec![] };
+        // Every key ever handed out, including ones whose items have expired or been removed.
+        let mut keys = vec![];
+        let mut next_item = 0;
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Insert(secs) => {
+                    let key = queue.insert(next_item, Duration::from_secs(secs));
+                    naive.entries.push((key, clock.now() + Duration::from_secs(secs), next_item));
+                    keys.push(key);
+                    next_item += 1;
+                }
+                Op::InsertAt(secs) => {
+                    let deadline = start + Duration::from_secs(secs);
+                    let key = queue.insert_at(next_item, deadline);
+                    naive.entries.push((key, deadline, next_item));
+                    keys.push(key);
+                    next_item += 1;
+                }
+                Op::Reset(index, secs) if !keys.is_empty() => {
+                    let key = *index.get(&keys);
+                    let deadline = clock.now() + Duration::from_secs(secs);
+                    let expected = naive.position(key).map(|pos| {
+                        let (key, _, item) = naive.entries.remove(pos);
+                        naive.entries.push((key, deadline, item));
+                    });
+                    prop_assert_eq!(queue.reset(key, deadline), expected.is_some(), "for operation {}", idx);
+                }
+                Op::Remove(index) if !keys.is_empty() => {
+                    let key = *index.get(&keys);
+                    let expected = naive.position(key).map(|pos| naive.entries.remove(pos).2);
+                    prop_assert_eq!(queue.remove(key), expected, "for operation {}", idx);
+                }
+                Op::Reset(..) | Op::Remove(_) => {}
+                Op::Advance(secs) => clock.advance(Duration::from_secs(secs)),
+                Op::PollExpired => {
+                    let now = clock.now();
+                    prop_assert_eq!(queue.poll_expired(now), naive.poll_expired(now), "for operation {}", idx);
+                }
+                Op::WaitNext => {
+                    // Waiting moves the clock forward to the next deadline, if it is in the future.
+                    let deadline = naive.next().map(|pos| naive.entries[pos].1);
+                    let expected_now = deadline.map_or(clock.now(), |deadline| deadline.max(clock.now()));
+                    let expected = naive.poll_expired(expected_now);
+                    prop_assert_eq!(queue.wait_next(), expected, "for operation {}", idx);
+                    prop_assert_eq!(clock.now(), expected_now, "for operation {}", idx);
+                }
+            }
+
+            prop_assert_eq!(queue.len(), naive.entries.len(), "for operation {}", idx);
+            let next_deadline = naive.next().map(|pos| naive.entries[pos].1);
+            prop_assert_eq!(queue.next_deadline(), next_deadline, "for operation {}", idx);
+            for &(key, deadline, item) in &naive.entries {
+                prop_assert_eq!(queue.get(key), Some(&item), "for operation {}", idx);
+                prop_assert_eq!(queue.deadline(key), Some(deadline), "for operation {}", idx);
+            }
+        }
+
+        // Drain whatever is left.
+        while let Some(item) = queue.wait_next() {
+            let pos = naive.next().unwrap();
+            prop_assert_eq!(item, naive.entries.remove(pos).2);
+        }
+        prop_assert!(naive.entries.is_empty());
+        for key in keys {
+            prop_assert!(!queue.contains(key), "every key is invalid once its item is gone");
+        }
+    }
+}
+
+#[test]
+fn test_insert_timeout_overflow() {
+    let clock = ManualClock::new();
+    let mut queue = DelayQueue::with_clock(&clock);
+    let never = queue.insert("never", Duration::MAX);
+    queue.insert("soon", Duration::from_secs(1));
+
+    let far_future = clock.now() + Duration::from_secs(60 * 60 * 24 * 365 * 29);
+    assert!(queue.deadline(never).unwrap() > far_future);
+    assert_eq!(queue.wait_next(), Some("soon"));
+    clock.advance(Duration::from_secs(60 * 60 * 24 * 365));
+    assert_eq!(queue.poll_expired(clock.now()), None);
+}
@@ -0,0 +1,121 @@
+use crate::{Handle, IndexedHeap};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest_derive::Arbitrary;
+
+/// Operations on an `IndexedHeap`. Operations that need a handle pick one of the live handles
+/// through an `Index`, which proptest shrinks towards the first handle.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 2)]
+    Push {
+        item: u16,
+    },
+    Pop,
+    Update {
+        handle: Index,
+        item: u16,
+    },
+    Remove {
+        handle: Index,
+    },
+}
+
+/// The model is a list of live handles along with the value each one refers to.
+struct TestState {
+    heap: IndexedHeap<u16>,
+    model: Vec<(Handle, u16)>,
+    dead: Vec<Handle>,
+}
+
+impl TestState {
+    fn new() -> Self {
+        Self {
+            heap: IndexedHeap::new(),
+            model: vec![],
+            dead: vec![],
+        }
+    }
+
+    fn apply_op_and_assert(&mut self, idx: usize, op: Op) {
+        match op {
+            Op::Push { item } => {
+                let handle = self.heap.push(item);
+                self.model.push((handle, item));
+            }
+            Op::Pop => {
+                let heap_item = self.heap.pop();
+                let max = self.model.iter().map(|&(_, item)| item).max();
+                assert_eq!(
+                    heap_item, max,
+                    "for operation {idx}, popped the greatest item"
+                );
+                if let Some(max) = max {
+                    // Any handle with the maximum value could have been popped: find the one that
+                    // the heap no longer contains.
+                    let pos = self
+                        .model
+                        .iter()
+                        .position(|&(handle, item)| item == max && !self.heap.contains(handle))
+                        .expect("popped handle is no longer in the heap");
+                    self.dead.push(self.model.swap_remove(pos).0);
+                }
+            }
+            Op::Update { handle, item } => {
+                if !self.model.is_empty() {
+                    let pos = handle.index(self.model.len());
+                    let entry = &mut self.model[pos];
+                    assert!(self.heap.update(entry.0, |x| *x = item));
+                    entry.1 = item;
+                }
+            }
+            Op::Remove { handle } => {
+                if !self.model.is_empty() {
+                    let (handle, item) = self.model.swap_remove(handle.index(self.model.len()));
+                    assert_eq!(self.heap.remove(handle), Some(item), "for operation {idx}");
+                    self.dead.push(handle);
+                }
+            }
+        }
+
+        assert_eq!(
+            self.heap.len(),
+            self.model.len(),
+            "for operation {idx}, lengths match"
+        );
+        for &(handle, item) in &self.model {
+            assert_eq!(
+                self.heap.get(handle),
+                Some(&item),
+                "for operation {idx}, live handle"
+            );
+        }
+        for &handle in &self.dead {
+            assert_eq!(
+                self.heap.get(handle),
+                None,
+                "for operation {idx}, dead handle"
+            );
+        }
+        let max = self.model.iter().map(|(_, item)| item).max();
+        assert_eq!(self.heap.peek(), max, "for operation {idx}, peek matches");
+    }
+
+    fn assert_final(self) {
+        let mut expected: Vec<_> = self.model.into_iter().map(|(_, item)| item).collect();
+        expected.sort();
+        assert_eq!(self.heap.into_sorted_vec(), expected, "sorted vecs match");
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_indexed_heap(ops in vec(any::<Op>(), 0..256)) {
+        let mut state = TestState::new();
+        for (idx, op) in ops.into_iter().enumerate() {
+            state.apply_op_and_assert(idx, op);
+        }
+        state.assert_final();
+    }
+}
@@ -0,0 +1,104 @@
+use super::NaiveHeap;
+use crate::LazyDeleteHeap;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest_derive::Arbitrary;
+
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    /// Items are drawn from a small range so that there are plenty of equal items.
+    #[proptest(weight = 3)]
+    Push(#[proptest(strategy = "0u8..16")] u8),
+    Pop,
+    /// Removes one of the items in the heap.
+    #[proptest(weight = 2)]
+    Remove(Index),
+    Compact,
+    SetCompactionThreshold(#[proptest(strategy = "0.0..=1.0f64")] f64),
+}
+
+proptest! {
+    /// Removed items should never be peeked at or popped, and should not count towards the length.
+    #[test]
+    fn test_lazy_delete_heap(initial in vec(0u8..16, 0..64), ops in vec(any::<Op>(), 0..256)) {
+        let mut heap: LazyDeleteHeap<u8> = initial.iter().copied().collect();
+        let mut naive = NaiveHeap::new();
+        naive.extend(initial);
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Push(item) => {
+                    heap.push(item);
+                    naive.push(item);
+                }
+                Op::Pop => {
+                    prop_assert_eq!(heap.pop(), naive.pop(), "for operation {}", idx);
+                }
+                Op::Remove(index) if !naive.data.is_empty() => {
+                    let item = naive.data.remove(index.index(naive.data.len()));
+                    heap.remove(&item);
+                }
+                Op::Remove(_) => {}
+                Op::Compact => {
+                    heap.compact();
+                    prop_assert_eq!(heap.tombstones(), 0, "for operation {}", idx);
+                }
+                Op::SetCompactionThreshold(threshold) => heap.set_compaction_threshold(threshold),
+            }
+
+            prop_assert_eq!(heap.peek(), naive.peek(), "for operation {}", idx);
+            prop_assert_eq!(heap.len(), naive.data.len(), "for operation {}", idx);
+            let stored = heap.len() + heap.tombstones();
+            prop_assert!(
+                heap.tombstones() as f64 <= heap.compaction_threshold() * stored as f64,
+                "for operation {}, {} tombstones out of {} stored items exceed the threshold",
+                idx,
+                heap.tombstones(),
+                stored,
+            );
+        }
+
+        let mut vec = heap.clone().into_vec();
+        vec.sort();
+        prop_assert_eq!(&vec, &naive.data, "unsorted vecs match");
+        prop_assert_eq!(heap.into_sorted_vec(), naive.into_sorted_vec(), "sorted vecs match");
+    }
+}
+
+#[test]
+fn test_lazy_delete_heap_absent_item() {
+    let mut heap = LazyDeleteHeap::new();
+    heap.extend([1, 2, 3]);
+    heap.set_compaction_threshold(1.0);
+
+    // Removing an item that isn't in the heap is a logic error, but compacting the heap gets rid
+    // of the stray tombstone, so that it doesn't remove an item that is pushed later.
+    heap.remove(&4);
+    heap.compact();
+    assert_eq!(heap.tombstones(), 0);
+    heap.push(4);
+    assert_eq!(heap.into_sorted_vec(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_lazy_delete_heap_threshold_one() {
+    let mut heap = LazyDeleteHeap::new();
+    heap.extend([1, 2, 3, 4]);
+    heap.set_compaction_threshold(1.0);
+
+    // Removing every item but the top one never compacts the heap.
+    for item in [1, 2, 3] {
+        heap.remove(&item);
+    }
+    assert_eq!(heap.tombstones(), 3);
+    assert_eq!(heap.len(), 1);
+
+    // Stray tombstones for items that aren't in the heap compact it once they outnumber the
+    // stored items.
+    heap.remove(&5);
+    assert_eq!(heap.tombstones(), 4);
+    heap.remove(&6);
+    assert_eq!(heap.tombstones(), 0);
+    assert_eq!(heap.into_sorted_vec(), [4]);
+}
@@ -0,0 +1,119 @@
+use crate::min_max_heap::{MinMaxHeap, PeekMaxMut, PeekMinMut};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_derive::Arbitrary;
+
+/// Operations on a `MinMaxHeap`. Items are drawn from a small range so that duplicates are common.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 3)]
+    Push {
+        #[proptest(strategy = "0u16..64")]
+        item: u16,
+    },
+    PopMin,
+    PopMax,
+    PushPopMin {
+        #[proptest(strategy = "0u16..64")]
+        item: u16,
+    },
+    PushPopMax {
+        #[proptest(strategy = "0u16..64")]
+        item: u16,
+    },
+    SetMin {
+        #[proptest(strategy = "0u16..64")]
+        item: u16,
+    },
+    SetMax {
+        #[proptest(strategy = "0u16..64")]
+        item: u16,
+    },
+    PeekMinPop,
+    PeekMaxPop,
+    /// Drains `front` items from the front and `back` items from the back, then drops the drain.
+    DrainSorted {
+        #[proptest(strategy = "0usize..4")]
+        front: usize,
+        #[proptest(strategy = "0usize..4")]
+        back: usize,
+    },
+}
+
+/// The model is a sorted vector, so the minimum is the first element and the maximum is the last.
+fn model_insert(model: &mut Vec<u16>, item: u16) {
+    let pos = model.partition_point(|&x| x <= item);
+    model.insert(pos, item);
+}
+
+proptest! {
+    #[test]
+    fn test_min_max_heap(initial in vec(0u16..64, 0..64), ops in vec(any::<Op>(), 0..256)) {
+        let mut heap = MinMaxHeap::from(initial.clone());
+        let mut model = initial;
+        model.sort();
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Push { item } => {
+                    heap.push(item);
+                    model_insert(&mut model, item);
+                }
+                Op::PopMin => {
+                    let expected = (!model.is_empty()).then(|| model.remove(0));
+                    prop_assert_eq!(heap.pop_min(), expected, "for operation {}", idx);
+                }
+                Op::PopMax => {
+                    prop_assert_eq!(heap.pop_max(), model.pop(), "for operation {}", idx);
+                }
+                Op::PushPopMin { item } => {
+                    model_insert(&mut model, item);
+                    prop_assert_eq!(heap.push_pop_min(item), model.remove(0), "for operation {}", idx);
+                }
+                Op::PushPopMax { item } => {
+                    model_insert(&mut model, item);
+                    prop_assert_eq!(Some(heap.push_pop_max(item)), model.pop(), "for operation {}", idx);
+                }
+                Op::SetMin { item } => {
+                    if let Some(mut min) = heap.peek_min_mut() {
+                        *min = item;
+                        model.remove(0);
+                        model_insert(&mut model, item);
+                    }
+                }
+                Op::SetMax { item } => {
+                    if let Some(mut max) = heap.peek_max_mut() {
+                        *max = item;
+                        model.pop();
+                        model_insert(&mut model, item);
+                    }
+                }
+                Op::PeekMinPop => {
+                    let expected = (!model.is_empty()).then(|| model.remove(0));
+                    prop_assert_eq!(heap.peek_min_mut().map(PeekMinMut::pop), expected, "for operation {}", idx);
+                }
+                Op::PeekMaxPop => {
+                    prop_assert_eq!(heap.peek_max_mut().map(PeekMaxMut::pop), model.pop(), "for operation {}", idx);
+                }
+                Op::DrainSorted { front, back } => {
+                    let mut drain = heap.drain_sorted();
+                    for _ in 0..front {
+                        prop_assert_eq!(drain.next(), model.pop(), "for operation {}", idx);
+                    }
+                    for _ in 0..back {
+                        let expected = (!model.is_empty()).then(|| model.remove(0));
+                        prop_assert_eq!(drain.next_back(), expected, "for operation {}", idx);
+                    }
+                    drop(drain);
+                    model.clear();
+                }
+            }
+
+            prop_assert_eq!(heap.len(), model.len(), "for operation {}", idx);
+            prop_assert_eq!(heap.peek_min(), model.first(), "for operation {}", idx);
+            prop_assert_eq!(heap.peek_max(), model.last(), "for operation {}", idx);
+        }
+
+        prop_assert_eq!(heap.into_sorted_vec(), model, "heap and model sorted vecs match");
+    }
+}
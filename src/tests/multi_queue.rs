@@ -0,0 +1,102 @@
+use super::NaiveHeap;
+use crate::MultiQueue;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_derive::Arbitrary;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    Push(u8),
+    Pop,
+}
+
+proptest! {
+    /// On a single thread, a pop returns `None` exactly when the queue is empty, and otherwise
+    /// one of the items that was pushed. With one shard, it is always the greatest.
+    #[test]
+    fn test_multi_queue(shards in 1usize..8, ops in vec(any::<Op>(), 0..256)) {
+        let queue = MultiQueue::with_shards(shards);
+        let mut naive = NaiveHeap::new();
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Push(item) => {
+                    queue.push(item);
+                    naive.push(item);
+                }
+                Op::Pop => match queue.pop() {
+                    Some(item) => {
+                        let pos = naive.data.binary_search(&item);
+                        prop_assert!(pos.is_ok(), "for operation {}, {} was pushed", idx, item);
+                        let expected = naive.data.remove(pos.unwrap());
+                        if shards == 1 {
+                            prop_assert!(naive.peek() <= Some(&expected), "for operation {}", idx);
+                        }
+                    }
+                    None => prop_assert!(naive.data.is_empty(), "for operation {}", idx),
+                },
+            }
+            prop_assert_eq!(queue.len(), naive.data.len(), "for operation {}", idx);
+        }
+
+        prop_assert_eq!(queue.into_sorted_vec(), naive.into_sorted_vec(), "sorted vecs match");
+    }
+}
+
+/// Many threads push and pop at the same time, and every item must come out exactly once.
+#[test]
+fn test_multi_queue_stress() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const ITEMS_PER_PRODUCER: usize = 10_000;
+
+    let queue = MultiQueue::with_shards(8);
+    let done = AtomicBool::new(false);
+
+    let mut popped = thread::scope(|s| {
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut popped = vec![];
+                    loop {
+                        // Check the flag before popping: if it was set and the queue is still
+                        // empty afterwards, no push can be in flight anymore.
+                        let done = done.load(Ordering::Acquire);
+                        match queue.pop() {
+                            Some(item) => popped.push(item),
+                            None if done => return popped,
+                            None => thread::yield_now(),
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..ITEMS_PER_PRODUCER {
+                        queue.push(producer * ITEMS_PER_PRODUCER + i);
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+
+        consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    popped.sort_unstable();
+    let expected: Vec<_> = (0..PRODUCERS * ITEMS_PER_PRODUCER).collect();
+    assert_eq!(popped, expected, "every item is popped exactly once");
+    assert!(queue.is_empty());
+}
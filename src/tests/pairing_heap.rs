@@ -0,0 +1,227 @@
+use crate::pairing_heap::{Handle, PairingHeap};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest_derive::Arbitrary;
+
+/// Operations on a `PairingHeap`. Operations that need a handle pick one of the live handles
+/// through an `Index`, which proptest shrinks towards the first handle.
+#[derive(Clone, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 3)]
+    Push {
+        item: u16,
+    },
+    #[proptest(weight = 2)]
+    Pop,
+    DecreaseKey {
+        handle: Index,
+        item: u16,
+    },
+    Meld {
+        #[proptest(strategy = "vec(any::<u16>(), 0..16)")]
+        items: Vec<u16>,
+    },
+    /// Pushes onto the second heap, whose handles the first heap must treat as absent.
+    PushOther {
+        item: u16,
+    },
+    /// Melds the second heap into the first, which keeps using it afterwards.
+    MeldOther,
+}
+
+/// The model is a list of live handles along with the value each one refers to, for the heap under
+/// test and for a second heap.
+struct TestState {
+    heap: PairingHeap<u16>,
+    model: Vec<(Handle<u16>, u16)>,
+    dead: Vec<Handle<u16>>,
+    other: PairingHeap<u16>,
+    other_model: Vec<(Handle<u16>, u16)>,
+}
+
+impl TestState {
+    fn new() -> Self {
+        Self {
+            heap: PairingHeap::new(),
+            model: vec![],
+            dead: vec![],
+            other: PairingHeap::new(),
+            other_model: vec![],
+        }
+    }
+
+    fn apply_op_and_assert(&mut self, idx: usize, op: Op) {
+        match op {
+            Op::Push { item } => {
+                let handle = self.heap.push(item);
+                self.model.push((handle, item));
+            }
+            Op::Pop => {
+                let heap_item = self.heap.pop();
+                let max = self.model.iter().map(|&(_, item)| item).max();
+                assert_eq!(
+                    heap_item, max,
+                    "for operation {idx}, popped the greatest item"
+                );
+                if let Some(max) = max {
+                    // Any handle with the maximum value could have been popped: find the one that
+                    // the heap no longer contains.
+                    let pos = self
+                        .model
+                        .iter()
+                        .position(|(handle, item)| *item == max && !self.heap.contains(handle))
+                        .expect("popped handle is no longer in the heap");
+                    self.dead.push(self.model.swap_remove(pos).0);
+                }
+            }
+            Op::DecreaseKey { handle, item } => {
+                if !self.model.is_empty() {
+                    let pos = handle.index(self.model.len());
+                    let entry = &mut self.model[pos];
+                    let result = self.heap.decrease_key(&entry.0, item);
+                    if item >= entry.1 {
+                        assert_eq!(result, Ok(entry.1), "for operation {idx}");
+                        entry.1 = item;
+                    } else {
+                        assert_eq!(result, Err(item), "for operation {idx}");
+                    }
+                }
+            }
+            Op::Meld { items } => {
+                let mut other = PairingHeap::new();
+                for item in items {
+                    let handle = other.push(item);
+                    self.model.push((handle, item));
+                }
+                self.heap.meld(other);
+            }
+            Op::PushOther { item } => {
+                let handle = self.other.push(item);
+                self.other_model.push((handle, item));
+            }
+            Op::MeldOther => {
+                self.heap.append(&mut self.other);
+                self.model.append(&mut self.other_model);
+            }
+        }
+
+        assert_eq!(
+            self.heap.len(),
+            self.model.len(),
+            "for operation {idx}, lengths match"
+        );
+        for (handle, _) in &self.model {
+            assert!(
+                self.heap.contains(handle),
+                "for operation {idx}, live handle"
+            );
+        }
+        for handle in &self.dead {
+            assert!(
+                !self.heap.contains(handle),
+                "for operation {idx}, dead handle"
+            );
+            assert_eq!(
+                self.heap.decrease_key(handle, u16::MAX),
+                Err(u16::MAX),
+                "for operation {idx}, dead handle"
+            );
+        }
+        let max = self.model.iter().map(|(_, item)| item).max();
+        assert_eq!(self.heap.peek(), max, "for operation {idx}, peek matches");
+
+        assert_eq!(
+            self.other.len(),
+            self.other_model.len(),
+            "for operation {idx}, lengths of the other heap match"
+        );
+        for (handle, _) in &self.other_model {
+            assert!(
+                self.other.contains(handle) && !self.heap.contains(handle),
+                "for operation {idx}, handle to the other heap"
+            );
+            assert_eq!(
+                self.heap.decrease_key(handle, u16::MAX),
+                Err(u16::MAX),
+                "for operation {idx}, handle to the other heap"
+            );
+        }
+        for (handle, _) in &self.model {
+            assert!(
+                !self.other.contains(handle),
+                "for operation {idx}, handle used with the other heap"
+            );
+            assert_eq!(
+                self.other.decrease_key(handle, u16::MAX),
+                Err(u16::MAX),
+                "for operation {idx}, handle used with the other heap"
+            );
+        }
+    }
+
+    fn assert_final(self) {
+        let mut expected: Vec<_> = self.model.into_iter().map(|(_, item)| item).collect();
+        expected.sort();
+        let mut unsorted: Vec<_> = self.heap.iter().copied().collect();
+        unsorted.sort();
+        assert_eq!(unsorted, expected, "iterated items match");
+        assert_eq!(self.heap.into_sorted_vec(), expected, "sorted vecs match");
+    }
+}
+
+#[test]
+fn test_handle_from_other_heap() {
+    let mut a = PairingHeap::new();
+    let mut b = PairingHeap::new();
+    let handle = a.push(1);
+    b.push(0);
+
+    assert!(!b.contains(&handle));
+    assert_eq!(b.decrease_key(&handle, 5), Err(5));
+    assert_eq!(a.peek(), Some(&1));
+    assert_eq!((a.len(), b.len()), (1, 1));
+
+    // The handle follows its element when its heap is melded into another one.
+    b.append(&mut a);
+    assert!(b.contains(&handle) && !a.contains(&handle));
+    assert_eq!(a.decrease_key(&handle, 5), Err(5));
+    assert_eq!(b.decrease_key(&handle, 5), Ok(1));
+    assert_eq!(b.into_sorted_vec(), [0, 5]);
+}
+
+/// Melds each heap into a new one, so that the first node's owner forwards through every heap.
+fn meld_chain(len: u32) -> (PairingHeap<u32>, Handle<u32>) {
+    let mut heap = PairingHeap::new();
+    let handle = heap.push(0);
+    for item in 1..len {
+        let mut next = PairingHeap::new();
+        next.push(item);
+        next.meld(heap);
+        heap = next;
+    }
+    (heap, handle)
+}
+
+#[test]
+fn test_long_meld_chain() {
+    let (mut heap, handle) = meld_chain(100_000);
+    assert!(heap.contains(&handle));
+    assert_eq!(heap.decrease_key(&handle, 100_000), Ok(0));
+    assert_eq!(heap.pop(), Some(100_000));
+
+    // Dropping the owners without a lookup that shortens the chain first doesn't overflow the
+    // stack.
+    drop(meld_chain(100_000));
+}
+
+proptest! {
+    #[test]
+    fn test_pairing_heap(ops in vec(any::<Op>(), 0..256)) {
+        let mut state = TestState::new();
+        for (idx, op) in ops.into_iter().enumerate() {
+            state.apply_op_and_assert(idx, op);
+        }
+        state.assert_final();
+    }
+}
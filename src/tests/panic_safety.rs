@@ -0,0 +1,421 @@
+//! Checks that a heap stays consistent when comparing or dropping its items panics.
+//!
+//! The heap moves items around through raw pointers, guarded by `Hole`, `RebuildOnDrop` and the
+//! `DropGuard` of `DrainSorted`, so that a panic halfway through never leaves an item duplicated or
+//! lost. The items here count how often they're alive, and panic on a chosen call to `cmp` or
+//! `drop`. After every operation, whether it panicked or not, each item that is still alive must be
+//! in the heap or have been handed back exactly once.
+//!
+//! A panic may leave the items out of heap order, which is allowed, so the order is only checked
+//! after rebuilding the heap at the end.
+//!
+//! The same checks run against [`PairingHeap`], whose nodes are linked through reference-counted
+//! pointers instead.
+
+use crate::pairing_heap::Handle;
+use crate::{BinaryHeap, PairingHeap, PeekMut};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest_derive::Arbitrary;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+
+/// The payload of the panics that the test injects, to tell them apart from real failures.
+struct InjectedPanic;
+
+#[derive(Clone, Copy, Debug, PartialEq, Arbitrary)]
+enum FaultKind {
+    Cmp,
+    Drop,
+}
+
+/// Makes the call to `cmp` or `drop` after `skip` others of the same kind panic.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+struct Fault {
+    kind: FaultKind,
+    #[proptest(strategy = "0usize..16")]
+    skip: usize,
+}
+
+/// Keeps track of every item created by a test, and injects the panics.
+#[derive(Default)]
+struct Tracker {
+    /// Whether each item is still alive, indexed by the item's id.
+    alive: RefCell<Vec<bool>>,
+    /// The number of times an item was dropped after it had already been dropped.
+    double_drops: Cell<usize>,
+    /// The next call that should panic. A fault only fires once, so that the heap never sees a
+    /// second panic while it is unwinding from the first, which would abort the test.
+    fault: Cell<Option<Fault>>,
+}
+
+impl Tracker {
+    fn item(&self, value: u8) -> Item<'_> {
+        let mut alive = self.alive.borrow_mut();
+        alive.push(true);
+        Item {
+            value,
+            id: alive.len() - 1,
+            tracker: self,
+        }
+    }
+
+    fn live_items(&self) -> usize {
+        self.alive.borrow().iter().filter(|&&alive| alive).count()
+    }
+
+    /// Panics if the pending fault is for a call of this kind and has no more calls to skip.
+    fn call(&self, kind: FaultKind) {
+        match self.fault.get() {
+            Some(fault) if fault.kind == kind && fault.skip == 0 => {
+                self.fault.set(None);
+                panic::panic_any(InjectedPanic);
+            }
+            Some(fault) if fault.kind == kind => self.fault.set(Some(Fault {
+                skip: fault.skip - 1,
+                ..fault
+            })),
+            _ => {}
+        }
+    }
+}
+
+/// An item that is compared by `value` alone, and told apart from equal items by `id`.
+struct Item<'a> {
+    value: u8,
+    id: usize,
+    tracker: &'a Tracker,
+}
+
+impl fmt::Debug for Item<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.value, self.id)
+    }
+}
+
+impl PartialEq for Item<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Item<'_> {}
+
+impl PartialOrd for Item<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Item<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.tracker.call(FaultKind::Cmp);
+        self.value.cmp(&other.value)
+    }
+}
+
+impl Drop for Item<'_> {
+    fn drop(&mut self) {
+        let was_alive = mem::replace(&mut self.tracker.alive.borrow_mut()[self.id], false);
+        if !was_alive {
+            self.tracker
+                .double_drops
+                .set(self.tracker.double_drops.get() + 1);
+        }
+        self.tracker.call(FaultKind::Drop);
+    }
+}
+
+/// Operations on the heap that compare or drop items. Values are drawn from a small range so that
+/// there are plenty of ties.
+#[derive(Clone, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 4)]
+    Push(#[proptest(strategy = "0u8..16")] u8),
+    #[proptest(weight = 2)]
+    Pop,
+    PushPop(#[proptest(strategy = "0u8..16")] u8),
+    Replace(#[proptest(strategy = "0u8..16")] u8),
+    /// Overwrites the greatest item through `peek_mut`, which drops the old item in place.
+    PeekMut(#[proptest(strategy = "0u8..16")] u8),
+    PeekMutPop,
+    Extend(#[proptest(strategy = "vec(0u8..16, 0..8)")] Vec<u8>),
+    Append(#[proptest(strategy = "vec(0u8..16, 0..8)")] Vec<u8>),
+    Retain(#[proptest(strategy = "1u8..4")] u8),
+    /// Takes some items from `drain_sorted`, and lets it drop the rest.
+    DrainSorted(#[proptest(strategy = "0usize..4")] usize),
+    IntoIterSorted(#[proptest(strategy = "0usize..4")] usize),
+    IntoSortedVec,
+    Clear,
+}
+
+/// One step of the test: an operation, and the panic to inject while it runs, if any.
+#[derive(Clone, Debug, Arbitrary)]
+struct Step {
+    op: Op,
+    fault: Option<Fault>,
+}
+
+/// Applies the operation, and returns the items it took out of the heap. Those are only dropped
+/// after the fault is disarmed, so that a `Drop` fault fires inside the heap's code.
+fn apply<'a>(heap: &mut BinaryHeap<Item<'a>>, tracker: &'a Tracker, op: Op) -> Vec<Item<'a>> {
+    match op {
+        Op::Push(value) => heap.push(tracker.item(value)),
+        Op::Pop => return heap.pop().into_iter().collect(),
+        Op::PushPop(value) => return vec![heap.push_pop(tracker.item(value))],
+        Op::Replace(value) => return heap.replace(tracker.item(value)).into_iter().collect(),
+        Op::PeekMut(value) => {
+            if let Some(mut top) = heap.peek_mut() {
+                *top = tracker.item(value);
+            }
+        }
+        Op::PeekMutPop => return heap.peek_mut().map(PeekMut::pop).into_iter().collect(),
+        Op::Extend(values) => heap.extend(values.into_iter().map(|value| tracker.item(value))),
+        Op::Append(values) => {
+            let items: Vec<_> = values
+                .into_iter()
+                .map(|value| tracker.item(value))
+                .collect();
+            heap.append(&mut BinaryHeap::from(items));
+        }
+        Op::Retain(modulus) => heap.retain(|item| item.value % modulus != 0),
+        Op::DrainSorted(count) => return heap.drain_sorted().take(count).collect(),
+        Op::IntoIterSorted(count) => {
+            let mut iter = mem::take(heap).into_iter_sorted();
+            let taken = iter.by_ref().take(count).collect();
+            *heap = iter.collect();
+            return taken;
+        }
+        Op::IntoSortedVec => *heap = BinaryHeap::from(mem::take(heap).into_sorted_vec()),
+        Op::Clear => heap.clear(),
+    }
+    Vec::new()
+}
+
+proptest! {
+    /// Runs the steps with their panics, and checks after each one that every live item is
+    /// accounted for exactly once.
+    #[test]
+    fn test_panic_safety(steps in vec(any::<Step>(), 0..64)) {
+        let tracker = Tracker::default();
+        let mut heap = BinaryHeap::new();
+        let mut panics = 0;
+
+        for (idx, Step { op, fault }) in steps.into_iter().enumerate() {
+            tracker.fault.set(fault);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| apply(&mut heap, &tracker, op)));
+            tracker.fault.set(None);
+            let taken = match result {
+                Ok(taken) => taken,
+                // Let panics that the test didn't inject fail the test.
+                Err(payload) if !payload.is::<InjectedPanic>() => panic::resume_unwind(payload),
+                Err(_) => {
+                    panics += 1;
+                    // With `debug-invariants`, the next operation would report the broken order.
+                    if cfg!(feature = "debug-invariants") {
+                        heap = BinaryHeap::from(mem::take(&mut heap).into_vec());
+                    }
+                    Vec::new()
+                }
+            };
+
+            prop_assert_eq!(
+                tracker.double_drops.get(),
+                0,
+                "for operation {}, no item is dropped twice",
+                idx
+            );
+            let mut ids: Vec<_> = heap.iter().chain(&taken).map(|item| item.id).collect();
+            ids.sort_unstable();
+            let len = ids.len();
+            ids.dedup();
+            prop_assert_eq!(ids.len(), len, "for operation {}, no item is duplicated", idx);
+            prop_assert_eq!(
+                len,
+                tracker.live_items(),
+                "for operation {}, every live item is in the heap or was taken out",
+                idx
+            );
+        }
+
+        // The heap is still usable. Rebuilding it restores an order that a panic may have broken.
+        let len = heap.len();
+        let mut heap = BinaryHeap::from(heap.into_vec());
+        heap.push(tracker.item(u8::MAX));
+        prop_assert_eq!(heap.peek().map(|item| item.value), Some(u8::MAX));
+        let values: Vec<_> = std::iter::from_fn(|| heap.pop()).map(|item| item.value).collect();
+        prop_assert_eq!(values.len(), len + 1);
+        prop_assert!(
+            values.windows(2).all(|w| w[0] >= w[1]),
+            "popped in order after {} panics",
+            panics
+        );
+
+        drop(heap);
+        prop_assert_eq!(tracker.live_items(), 0, "no item is leaked");
+        prop_assert_eq!(tracker.double_drops.get(), 0, "no item is dropped twice");
+    }
+}
+
+/// With `debug-invariants`, a comparison that panics while `drain_sorted` pops must not make the
+/// `DrainSorted` destructor, which pops the rest while unwinding, panic again and abort.
+#[test]
+fn test_drain_sorted_cmp_panic() {
+    let tracker = Tracker::default();
+    let mut heap: BinaryHeap<_> = (0..16).map(|value| tracker.item(value)).collect();
+    tracker.fault.set(Some(Fault {
+        kind: FaultKind::Cmp,
+        skip: 2,
+    }));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        heap.drain_sorted().take(4).collect::<Vec<_>>()
+    }));
+    assert!(result.is_err_and(|payload| payload.is::<InjectedPanic>()));
+    assert!(heap.is_empty());
+    assert_eq!(tracker.live_items(), 0);
+    assert_eq!(tracker.double_drops.get(), 0);
+}
+
+/// Operations on a `PairingHeap` that compare or drop items.
+#[derive(Clone, Debug, Arbitrary)]
+enum PairingOp {
+    #[proptest(weight = 4)]
+    Push(#[proptest(strategy = "0u8..16")] u8),
+    #[proptest(weight = 2)]
+    Pop,
+    /// Picks one of the handles pushed so far, which may no longer be valid.
+    DecreaseKey(Index, #[proptest(strategy = "0u8..16")] u8),
+    Meld(#[proptest(strategy = "vec(0u8..16, 0..8)")] Vec<u8>),
+    IntoSortedVec,
+    Clear,
+}
+
+#[derive(Clone, Debug, Arbitrary)]
+struct PairingStep {
+    op: PairingOp,
+    fault: Option<Fault>,
+}
+
+/// Applies the operation, and returns the items it took out of the heap, like [`apply`].
+fn apply_pairing<'a>(
+    heap: &mut PairingHeap<Item<'a>>,
+    handles: &mut Vec<Handle<Item<'a>>>,
+    tracker: &'a Tracker,
+    op: PairingOp,
+) -> Vec<Item<'a>> {
+    match op {
+        PairingOp::Push(value) => handles.push(heap.push(tracker.item(value))),
+        PairingOp::Pop => return heap.pop().into_iter().collect(),
+        PairingOp::DecreaseKey(handle, value) => {
+            if !handles.is_empty() {
+                let handle = &handles[handle.index(handles.len())];
+                let (Ok(item) | Err(item)) = heap.decrease_key(handle, tracker.item(value));
+                return vec![item];
+            }
+        }
+        PairingOp::Meld(values) => {
+            let mut other = PairingHeap::new();
+            for value in values {
+                handles.push(other.push(tracker.item(value)));
+            }
+            heap.meld(other);
+        }
+        PairingOp::IntoSortedVec => {
+            *heap = PairingHeap::from(mem::take(heap).into_sorted_vec());
+        }
+        PairingOp::Clear => heap.clear(),
+    }
+    Vec::new()
+}
+
+proptest! {
+    /// Runs the steps against a `PairingHeap`, and checks after each one that every live item is
+    /// accounted for exactly once, and that the length is accurate.
+    #[test]
+    fn test_pairing_heap_panic_safety(steps in vec(any::<PairingStep>(), 0..64)) {
+        let tracker = Tracker::default();
+        let mut heap = PairingHeap::new();
+        let mut handles = Vec::new();
+        let mut panics = 0;
+
+        for (idx, PairingStep { op, fault }) in steps.into_iter().enumerate() {
+            tracker.fault.set(fault);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                apply_pairing(&mut heap, &mut handles, &tracker, op)
+            }));
+            tracker.fault.set(None);
+            let taken = match result {
+                Ok(taken) => taken,
+                Err(payload) if !payload.is::<InjectedPanic>() => panic::resume_unwind(payload),
+                Err(_) => {
+                    panics += 1;
+                    Vec::new()
+                }
+            };
+
+            prop_assert_eq!(
+                tracker.double_drops.get(),
+                0,
+                "for operation {}, no item is dropped twice",
+                idx
+            );
+            prop_assert_eq!(
+                heap.len(),
+                heap.iter().count(),
+                "for operation {}, the length is accurate",
+                idx
+            );
+            prop_assert_eq!(heap.peek().is_some(), !heap.is_empty(), "for operation {}", idx);
+            let mut ids: Vec<_> = heap.iter().chain(&taken).map(|item| item.id).collect();
+            ids.sort_unstable();
+            let len = ids.len();
+            ids.dedup();
+            prop_assert_eq!(ids.len(), len, "for operation {}, no item is duplicated", idx);
+            prop_assert_eq!(
+                len,
+                tracker.live_items(),
+                "for operation {}, every live item is in the heap or was taken out",
+                idx
+            );
+        }
+
+        // The heap is still usable. Rebuilding it restores an order that a panic may have broken.
+        let len = heap.len();
+        let mut heap = PairingHeap::from(heap.into_vec());
+        heap.push(tracker.item(u8::MAX));
+        prop_assert_eq!(heap.peek().map(|item| item.value), Some(u8::MAX));
+        let values: Vec<_> = std::iter::from_fn(|| heap.pop()).map(|item| item.value).collect();
+        prop_assert_eq!(values.len(), len + 1);
+        prop_assert!(
+            values.windows(2).all(|w| w[0] >= w[1]),
+            "popped in order after {} panics",
+            panics
+        );
+
+        drop(heap);
+        drop(handles);
+        prop_assert_eq!(tracker.live_items(), 0, "no item is leaked");
+        prop_assert_eq!(tracker.double_drops.get(), 0, "no item is dropped twice");
+    }
+}
+
+/// A comparison that panics while pushing leaves every item in the heap, including the new one.
+#[test]
+fn test_pairing_heap_push_cmp_panic() {
+    let tracker = Tracker::default();
+    let mut heap: PairingHeap<_> = (0..10).map(|value| tracker.item(value)).collect();
+    tracker.fault.set(Some(Fault {
+        kind: FaultKind::Cmp,
+        skip: 0,
+    }));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| heap.push(tracker.item(20))));
+    assert!(result.is_err_and(|payload| payload.is::<InjectedPanic>()));
+    assert_eq!(heap.len(), 11);
+    assert_eq!(heap.iter().count(), 11);
+    assert_eq!(tracker.live_items(), 11);
+    assert!(heap.peek().is_some());
+}
@@ -0,0 +1,71 @@
+use crate::PriorityQueue;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_derive::Arbitrary;
+use std::collections::HashMap;
+
+/// Operations on a `PriorityQueue`. Keys are drawn from a small range so that pushes frequently
+/// update existing keys.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    #[proptest(weight = 3)]
+    Push {
+        #[proptest(strategy = "0u8..16")]
+        key: u8,
+        priority: u16,
+    },
+    ChangePriority {
+        #[proptest(strategy = "0u8..16")]
+        key: u8,
+        priority: u16,
+    },
+    #[proptest(weight = 2)]
+    Pop,
+    Remove {
+        #[proptest(strategy = "0u8..16")]
+        key: u8,
+    },
+}
+
+/// Returns the greatest priority in the model.
+fn model_max(model: &HashMap<u8, u16>) -> Option<u16> {
+    model.values().copied().max()
+}
+
+proptest! {
+    #[test]
+    fn test_priority_queue(ops in vec(any::<Op>(), 0..256)) {
+        let mut queue = PriorityQueue::new();
+        let mut model = HashMap::new();
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Push { key, priority } => {
+                    prop_assert_eq!(queue.push(key, priority), model.insert(key, priority), "for operation {}", idx);
+                }
+                Op::ChangePriority { key, priority } => {
+                    let expected = model.get_mut(&key).map(|p| std::mem::replace(p, priority));
+                    prop_assert_eq!(queue.change_priority(&key, priority), expected, "for operation {}", idx);
+                }
+                Op::Pop => {
+                    let expected = model_max(&model);
+                    let popped = queue.pop();
+                    prop_assert_eq!(popped.map(|(_, p)| p), expected, "for operation {}", idx);
+                    if let Some((key, priority)) = popped {
+                        prop_assert_eq!(model.remove(&key), Some(priority), "for operation {}", idx);
+                    }
+                }
+                Op::Remove { key } => {
+                    let expected = model.remove(&key).map(|p| (key, p));
+                    prop_assert_eq!(queue.remove(&key), expected, "for operation {}", idx);
+                }
+            }
+
+            prop_assert_eq!(queue.len(), model.len(), "for operation {}", idx);
+            for (key, priority) in &model {
+                prop_assert_eq!(queue.priority(key), Some(priority), "for operation {}", idx);
+            }
+            prop_assert_eq!(queue.peek().map(|(_, p)| *p), model_max(&model), "for operation {}", idx);
+        }
+    }
+}
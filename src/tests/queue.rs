@@ -0,0 +1,65 @@
+//! Every heap in the crate that implements `PriorityQueue`, run through the shared model test.
+
+use crate::testing::{self, NaiveHeap};
+use crate::{
+    BinaryHeap, DaryHeap, IndexedHeap, MinComparator, MinMaxHeap, PairingHeap, StableBinaryHeap,
+};
+use proptest::prelude::*;
+
+#[test]
+fn test_binary_heap() {
+    testing::check_priority_queue(BinaryHeap::new, any::<u8>());
+    testing::check_priority_queue_with_cmp(BinaryHeap::new_min, MinComparator, any::<u8>());
+}
+
+#[test]
+fn test_dary_heap() {
+    testing::check_priority_queue(DaryHeap::<_, 3>::new, any::<u8>());
+    testing::check_priority_queue(DaryHeap::<_, 4>::new, any::<u8>());
+}
+
+#[test]
+fn test_min_max_heap() {
+    testing::check_priority_queue(MinMaxHeap::new, any::<u8>());
+}
+
+#[test]
+fn test_pairing_heap() {
+    testing::check_priority_queue(PairingHeap::new, any::<u8>());
+    testing::check_priority_queue_with_cmp(PairingHeap::new_min, MinComparator, any::<u8>());
+}
+
+#[test]
+fn test_stable_heap() {
+    testing::check_priority_queue(StableBinaryHeap::new, any::<u8>());
+}
+
+#[test]
+fn test_indexed_heap() {
+    testing::check_priority_queue(IndexedHeap::new, any::<u8>());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_lazy_delete_heap() {
+    testing::check_priority_queue(crate::LazyDeleteHeap::new, any::<u8>());
+}
+
+/// The reference checks out against itself, which at least makes sure the harness accepts a
+/// correct queue for both orders.
+#[test]
+fn test_naive_heap() {
+    testing::check_priority_queue(NaiveHeap::new, any::<u8>());
+    testing::check_priority_queue_with_cmp(
+        || NaiveHeap::with_cmp(MinComparator),
+        MinComparator,
+        any::<u8>(),
+    );
+}
+
+/// A queue that pops its smallest item instead of its greatest is caught.
+#[test]
+#[should_panic(expected = "minimal failing input")]
+fn test_wrong_order_is_caught() {
+    testing::check_priority_queue(BinaryHeap::new_min, any::<u8>());
+}
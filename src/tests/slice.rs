@@ -0,0 +1,65 @@
+use crate::binary_heap::slice;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Returns the length of the longest prefix of `v` that is a max-heap, by checking every prefix.
+fn naive_is_heap_until(v: &[u16]) -> usize {
+    (0..=v.len())
+        .rev()
+        .find(|&n| (1..n).all(|i| v[(i - 1) / 2] >= v[i]))
+        .unwrap_or(0)
+}
+
+proptest! {
+    #[test]
+    fn test_is_heap_until(v in vec(0u16..16, 0..32)) {
+        prop_assert_eq!(slice::is_heap_until(&v), naive_is_heap_until(&v));
+        prop_assert_eq!(slice::is_heap(&v), naive_is_heap_until(&v) == v.len());
+    }
+
+    /// Building a heap by pushing one element at a time, or all at once, should produce a heap that
+    /// pops its elements in descending order and sorts them in ascending order.
+    #[test]
+    fn test_slice_heap_algorithms(items in vec(any::<u16>(), 0..128)) {
+        let mut ascending = items.clone();
+        ascending.sort();
+
+        let mut pushed = items.clone();
+        for end in 1..=pushed.len() {
+            slice::push_heap(&mut pushed[..end]);
+            prop_assert!(slice::is_heap(&pushed[..end]), "prefix of length {} is a heap", end);
+        }
+
+        let mut made = items.clone();
+        slice::make_heap(&mut made);
+        prop_assert!(slice::is_heap(&made));
+
+        let mut popped = made.clone();
+        for end in (1..=popped.len()).rev() {
+            slice::pop_heap(&mut popped[..end]);
+            prop_assert!(slice::is_heap(&popped[..end - 1]), "prefix of length {} is a heap", end - 1);
+        }
+        prop_assert_eq!(&popped, &ascending, "popping every element sorts the slice");
+
+        slice::sort_heap(&mut pushed);
+        prop_assert_eq!(&pushed, &ascending, "sort_heap sorts a pushed heap");
+        slice::sort_heap(&mut made);
+        prop_assert_eq!(&made, &ascending, "sort_heap sorts a made heap");
+
+        let mut descending = items;
+        slice::make_heap_by(&mut descending, |a, b| b.cmp(a));
+        prop_assert!(slice::is_heap_by(&descending, |a, b| b.cmp(a)));
+        prop_assert_eq!(slice::is_heap_until_by(&descending, |a, b| b.cmp(a)), descending.len());
+        if let Some(last) = descending.len().checked_sub(1) {
+            descending[last] = 0;
+            slice::push_heap_by(&mut descending, |a, b| b.cmp(a));
+            prop_assert_eq!(descending[0], 0);
+            slice::pop_heap_by(&mut descending, |a, b| b.cmp(a));
+            prop_assert_eq!(descending[last], 0);
+            descending[last] = 0;
+            slice::push_heap_by(&mut descending, |a, b| b.cmp(a));
+        }
+        slice::sort_heap_by(&mut descending, |a, b| b.cmp(a));
+        prop_assert!(descending.windows(2).all(|w| w[0] >= w[1]), "sort_heap_by sorts by the comparator");
+    }
+}
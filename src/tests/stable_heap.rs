@@ -0,0 +1,96 @@
+use crate::{KeyComparator, StableBinaryHeap};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_derive::Arbitrary;
+
+/// An item in the test: a priority, which is all the heap compares, and a unique id that tells
+/// equal items apart.
+type Item = (u8, u32);
+
+/// A variant of `NaiveHeap` that keeps equal items in the order they were pushed.
+///
+/// The data is kept in descending order of priority with a stable sort, so the greatest item that
+/// was pushed first is always at the front.
+struct NaiveStableHeap {
+    data: Vec<Item>,
+}
+
+impl NaiveStableHeap {
+    fn new() -> Self {
+        Self { data: vec![] }
+    }
+
+    fn push(&mut self, item: Item) {
+        self.data.push(item);
+        // `sort_by_key` is stable, so equal items stay in the order they were pushed.
+        self.data.sort_by_key(|item| std::cmp::Reverse(item.0));
+    }
+
+    fn pop(&mut self) -> Option<Item> {
+        (!self.data.is_empty()).then(|| self.data.remove(0))
+    }
+
+    fn peek(&self) -> Option<&Item> {
+        self.data.first()
+    }
+
+    fn into_sorted_vec(self) -> Vec<Item> {
+        let mut data = self.data;
+        data.sort_by_key(|item| item.0);
+        data
+    }
+}
+
+#[derive(Clone, Copy, Debug, Arbitrary)]
+enum Op {
+    /// Priorities are drawn from a small range so that there are plenty of ties.
+    #[proptest(weight = 2)]
+    Push {
+        #[proptest(strategy = "0u8..8")]
+        priority: u8,
+    },
+    Pop,
+    Clear,
+}
+
+proptest! {
+    /// Items with equal priorities should come out of the heap in the order they went in, both
+    /// when popping and when sorting.
+    #[test]
+    fn test_stable_heap(ops in vec(any::<Op>(), 0..256)) {
+        let mut heap = StableBinaryHeap::with_cmp(KeyComparator(|item: &Item| item.0));
+        let mut naive = NaiveStableHeap::new();
+        let mut next_id = 0;
+
+        for (idx, op) in ops.into_iter().enumerate() {
+            match op {
+                Op::Push { priority } => {
+                    heap.push((priority, next_id));
+                    naive.push((priority, next_id));
+                    next_id += 1;
+                }
+                Op::Pop => {
+                    prop_assert_eq!(heap.pop(), naive.pop(), "for operation {}", idx);
+                }
+                Op::Clear => {
+                    heap.clear();
+                    naive.data.clear();
+                }
+            }
+            prop_assert_eq!(heap.peek(), naive.peek(), "for operation {}", idx);
+            prop_assert_eq!(heap.len(), naive.data.len(), "for operation {}", idx);
+        }
+
+        let mut items: Vec<_> = heap.iter().copied().collect();
+        items.sort();
+        let mut expected = naive.data.clone();
+        expected.sort();
+        prop_assert_eq!(&items, &expected, "iterated items match");
+
+        let mut vec = heap.clone().into_vec();
+        vec.sort();
+        prop_assert_eq!(&vec, &expected, "unsorted vecs match");
+
+        prop_assert_eq!(heap.into_sorted_vec(), naive.into_sorted_vec(), "sorted vecs match");
+    }
+}
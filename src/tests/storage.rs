@@ -0,0 +1,94 @@
+use super::{HeapModel, NaiveHeap, Op, TestState, TestStorage};
+use crate::binary_heap::storage::{ArrayStorage, HeapStorage, SmallStorage};
+use crate::{BinaryHeap, MaxComparator};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest_state_machine::ReferenceStateMachine;
+use std::sync::atomic::AtomicUsize;
+
+impl<const N: usize> TestStorage for ArrayStorage<usize, N> {}
+impl<const N: usize> TestStorage for SmallStorage<usize, N> {}
+
+/// Runs the operations against a heap backed by `storage`, and checks that popping everything
+/// that's left matches the naive heap.
+fn run<S: TestStorage>(
+    storage: S,
+    initial: &NaiveHeap<usize>,
+    ops: &[Op],
+    seen_counter: Option<&AtomicUsize>,
+) -> S {
+    let mut state = TestState::<2, S>::from_storage(storage, initial);
+    state.apply_ops_and_assert(ops.to_vec(), seen_counter);
+
+    let mut expected = state.naive.into_sorted_vec();
+    expected.reverse();
+    let popped: Vec<_> = state.heap.drain_sorted().collect();
+    assert_eq!(popped, expected, "heap and naive drain in the same order");
+    state.heap.into_storage()
+}
+
+proptest! {
+    /// Every storage should behave exactly like the default `Vec`, for the operations they share.
+    ///
+    /// All three storages run the same operations. Counting them once for each storage only makes
+    /// shrinking keep a few more operations around at first.
+    #[test]
+    fn test_storages((initial, ops, seen_counter) in HeapModel::<false>::sequential_strategy(1..128)) {
+        let seen_counter = seen_counter.as_deref();
+        // At most 128 initial items, and 128 operations that add at most 3 items each.
+        run(ArrayStorage::<usize, 512>::new(), &initial, &ops, seen_counter);
+
+        let small = run(SmallStorage::<usize, 8>::new(), &initial, &ops, seen_counter);
+        prop_assert!(small.as_slice().is_empty());
+
+        let mut buf = Vec::new();
+        run(&mut buf, &initial, &ops, seen_counter);
+        prop_assert!(buf.is_empty(), "the borrowed vector is drained");
+    }
+
+    /// `retain` removes elements through `swap_remove`, which should leave a valid heap behind with
+    /// any storage.
+    #[test]
+    fn test_storage_retain(items in vec(any::<u16>(), 0..64), modulus in 1u16..8) {
+        let mut expected: Vec<_> = items.iter().copied().filter(|x| x % modulus == 0).collect();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let mut heap = BinaryHeap::from_storage(SmallStorage::<u16, 16>::new(), MaxComparator);
+        heap.extend(&items);
+        heap.retain(|x| x % modulus == 0);
+        prop_assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), expected.clone());
+
+        let mut buf = items.clone();
+        let mut heap = BinaryHeap::from_storage(&mut buf, MaxComparator);
+        heap.retain(|x| x % modulus == 0);
+        prop_assert_eq!(heap.len(), expected.len());
+        prop_assert_eq!(BinaryHeap::from(buf).into_sorted_vec(), expected.into_iter().rev().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_small_storage_spills() {
+    let mut heap = BinaryHeap::from_storage(SmallStorage::<u32, 4>::new(), MaxComparator);
+    heap.extend([5, 1, 4, 2]);
+    assert!(!heap.as_storage().is_spilled());
+    heap.push(3);
+    assert!(heap.as_storage().is_spilled());
+    assert!(heap.as_storage().capacity() >= 5);
+    assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+
+    let mut storage = SmallStorage::<u32, 4>::new();
+    storage.reserve(4);
+    assert!(
+        !storage.is_spilled(),
+        "reserving within the inline capacity"
+    );
+    storage.reserve(5);
+    assert!(storage.is_spilled());
+}
+
+#[test]
+#[should_panic(expected = "ArrayStorage is full")]
+fn test_array_storage_full() {
+    let mut heap = BinaryHeap::from_storage(ArrayStorage::<u32, 2>::new(), MaxComparator);
+    heap.extend([1, 2, 3]);
+}
@@ -0,0 +1,151 @@
+use crate::SyncBinaryHeap;
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PRODUCERS: usize = 4;
+const CONSUMERS: usize = 4;
+const ITEMS_PER_PRODUCER: usize = 2_000;
+
+/// Runs producers and consumers against `queue` at the same time, then checks that every item
+/// that was pushed was popped exactly once.
+fn check_exactly_once(queue: &SyncBinaryHeap<usize>) {
+    let mut popped = thread::scope(|s| {
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut popped = vec![];
+                    while let Some(item) = queue.pop() {
+                        popped.push(item);
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                s.spawn(move || {
+                    for i in 0..ITEMS_PER_PRODUCER {
+                        queue
+                            .push(producer * ITEMS_PER_PRODUCER + i)
+                            .expect("the queue is open");
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        queue.close();
+
+        consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    popped.sort_unstable();
+    let expected: Vec<_> = (0..PRODUCERS * ITEMS_PER_PRODUCER).collect();
+    assert_eq!(popped, expected, "every item is popped exactly once");
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_unbounded_exactly_once() {
+    check_exactly_once(&SyncBinaryHeap::new());
+}
+
+#[test]
+fn test_bounded_exactly_once() {
+    let queue = SyncBinaryHeap::bounded(8);
+    check_exactly_once(&queue);
+    assert_eq!(queue.capacity(), Some(8));
+}
+
+#[test]
+fn test_bounded_push_blocks() {
+    let queue = SyncBinaryHeap::bounded(2);
+    queue.push(1).unwrap();
+    queue.push(2).unwrap();
+
+    thread::scope(|s| {
+        let pusher = s.spawn(|| queue.push(3));
+        // The pusher can't make progress until there is room.
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(pusher.join().unwrap(), Ok(()));
+    });
+    assert_eq!(queue.into_heap().into_sorted_vec(), [1, 3]);
+}
+
+#[test]
+fn test_close_wakes_everyone() {
+    let queue = SyncBinaryHeap::bounded(1);
+    queue.push(0).unwrap();
+    let barrier = Barrier::new(CONSUMERS + 2);
+
+    thread::scope(|s| {
+        // Pushers are blocked on a full queue, and poppers are blocked until the queue is drained.
+        let pusher = s.spawn(|| {
+            barrier.wait();
+            queue.push(1)
+        });
+        let poppers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    queue.pop()
+                })
+            })
+            .collect();
+
+        barrier.wait();
+        thread::sleep(Duration::from_millis(20));
+        queue.close();
+        assert!(queue.is_closed());
+
+        // The pusher either got in before the queue was closed, or had its item handed back.
+        let pushed = pusher.join().unwrap().is_ok();
+        let mut popped: Vec<_> = poppers
+            .into_iter()
+            .filter_map(|popper| popper.join().unwrap())
+            .collect();
+        popped.extend(queue.try_pop());
+        popped.sort_unstable();
+        let expected: &[i32] = if pushed { &[0, 1] } else { &[0] };
+        assert_eq!(popped, expected);
+    });
+
+    assert_eq!(queue.push(2), Err(2), "pushing to a closed queue fails");
+    assert_eq!(
+        queue.pop(),
+        None,
+        "popping from a closed, empty queue doesn't block"
+    );
+}
+
+#[test]
+fn test_pop_timeout() {
+    let queue = SyncBinaryHeap::new();
+
+    let start = Instant::now();
+    assert_eq!(queue.pop_timeout(Duration::from_millis(20)), None);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            thread::sleep(Duration::from_millis(10));
+            queue.push(7).unwrap();
+        });
+        assert_eq!(queue.pop_timeout(Duration::from_secs(60)), Some(7));
+    });
+
+    queue.push(1).unwrap();
+    queue.push(3).unwrap();
+    queue.close();
+    assert_eq!(queue.pop_timeout(Duration::from_secs(60)), Some(3));
+    assert_eq!(queue.try_pop(), Some(1));
+    assert_eq!(queue.pop_timeout(Duration::from_secs(60)), None);
+}
@@ -0,0 +1,56 @@
+use crate::TopK;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+proptest! {
+    /// Offering items one by one should keep the same items as sorting all of them and truncating
+    /// to the bound, and every item that is not kept should be handed back exactly once.
+    #[test]
+    fn test_top_k(k in 0usize..16, items in vec(0u16..64, 0..128)) {
+        let mut top = TopK::new(k);
+        let mut offered = vec![];
+        let mut evicted = vec![];
+
+        for (idx, item) in items.into_iter().enumerate() {
+            let threshold = top.threshold().copied();
+            let result = top.offer(item);
+            offered.push(item);
+
+            match threshold {
+                None => prop_assert!(result.is_none() || k == 0, "for operation {}", idx),
+                Some(threshold) if item > threshold => {
+                    prop_assert_eq!(result, Some(threshold), "for operation {}", idx)
+                }
+                Some(_) => prop_assert_eq!(result, Some(item), "for operation {}", idx),
+            }
+            evicted.extend(result);
+
+            let mut expected = offered.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+            expected.truncate(k);
+            prop_assert_eq!(top.len(), expected.len(), "for operation {}", idx);
+            let expected_threshold = if expected.len() == k { expected.last() } else { None };
+            prop_assert_eq!(top.threshold(), expected_threshold, "for operation {}", idx);
+        }
+
+        let kept = top.into_sorted_vec();
+        let mut expected = offered.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.truncate(k);
+        prop_assert_eq!(&kept, &expected, "kept items are the greatest");
+
+        let mut all: Vec<_> = kept.into_iter().chain(evicted).collect();
+        all.sort();
+        offered.sort();
+        prop_assert_eq!(all, offered, "every item is kept or evicted");
+    }
+}
+
+#[test]
+fn test_top_k_huge_k() {
+    let mut top = TopK::new(usize::MAX / 2);
+    for item in [3, 1, 2] {
+        assert_eq!(top.offer(item), None);
+    }
+    assert_eq!(top.into_sorted_vec(), [3, 2, 1]);
+}
@@ -0,0 +1,274 @@
+//! A bounded collection that keeps only the *k* greatest items it is offered.
+//!
+//! [`TopK`] is backed by a min-ordered [`BinaryHeap`], so the smallest retained item is always at
+//! the top. Once the collection is full, an offered item that beats it replaces it in place through
+//! [`PeekMut`](crate::PeekMut), which costs a single sift down rather than a push followed by a
+//! pop.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_binary_heap_example::TopK;
+//!
+//! let mut top = TopK::new(3);
+//! for sample in [12, 7, 31, 4, 18, 25] {
+//!     top.offer(sample);
+//! }
+//!
+//! assert_eq!(top.threshold(), Some(&18));
+//! assert_eq!(top.into_sorted_vec(), [31, 25, 18]);
+//! ```
+
+use core::fmt;
+use core::mem;
+
+use alloc::vec::Vec;
+
+use crate::binary_heap::{BinaryHeap, Iter};
+use crate::compare::MinComparator;
+
+/// A collection that keeps at most `k` items: the greatest ones it has been offered.
+///
+/// Among equal items, the ones offered first are kept.
+///
+/// # Time complexity
+///
+/// | [offer]       | [threshold] |
+/// |---------------|-------------|
+/// | *O*(log(*k*)) | *O*(1)      |
+///
+/// [offer]: TopK::offer
+/// [threshold]: TopK::threshold
+pub struct TopK<T> {
+    heap: BinaryHeap<T, MinComparator>,
+    k: usize,
+}
+
+impl<T: Clone> Clone for TopK<T> {
+    fn clone(&self) -> Self {
+        TopK {
+            heap: self.heap.clone(),
+            k: self.k,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TopK<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopK")
+            .field("k", &self.k)
+            .field("items", &self.heap)
+            .finish()
+    }
+}
+
+impl<T: Ord> TopK<T> {
+    /// Creates an empty `TopK` that keeps at most `k` items.
+    ///
+    /// Room for the items is allocated as they are offered, so a large `k` costs nothing up front.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let mut top = TopK::new(2);
+    /// top.offer(4);
+    /// ```
+    #[must_use]
+    pub fn new(k: usize) -> TopK<T> {
+        TopK {
+            heap: BinaryHeap::new_min(),
+            k,
+        }
+    }
+
+    /// Offers an item, keeping it if it is among the `k` greatest items seen so far.
+    ///
+    /// Returns the item that no longer fits: either the previously smallest retained item, which
+    /// `item` replaced, or `item` itself if it does not beat the [`threshold`](TopK::threshold).
+    /// Returns `None` if the collection was not full yet.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let mut top = TopK::new(2);
+    ///
+    /// assert_eq!(top.offer(5), None);
+    /// assert_eq!(top.offer(3), None);
+    /// assert_eq!(top.offer(8), Some(3));
+    /// assert_eq!(top.offer(1), Some(1));
+    /// assert_eq!(top.into_sorted_vec(), [8, 5]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// The worst case cost of `offer` is *O*(log(*k*)).
+    pub fn offer(&mut self, mut item: T) -> Option<T> {
+        if self.heap.len() < self.k {
+            self.heap.push(item);
+            return None;
+        }
+        match self.heap.peek_mut() {
+            Some(mut smallest) if item > *smallest => {
+                mem::swap(&mut *smallest, &mut item);
+            }
+            _ => {}
+        }
+        Some(item)
+    }
+
+    /// Returns the item that a newly offered item has to beat in order to be kept, or `None` if
+    /// the collection is not full yet and will keep any item.
+    ///
+    /// This is the smallest retained item once `k` items have been offered. A `TopK` with a bound
+    /// of zero keeps no items, so its threshold is always `None`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let mut top = TopK::new(2);
+    ///
+    /// top.offer(5);
+    /// assert_eq!(top.threshold(), None);
+    /// top.offer(3);
+    /// assert_eq!(top.threshold(), Some(&3));
+    /// top.offer(8);
+    /// assert_eq!(top.threshold(), Some(&5));
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Cost is *O*(1) in the worst case.
+    #[must_use]
+    pub fn threshold(&self) -> Option<&T> {
+        if self.is_full() {
+            self.heap.peek()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the `TopK` and returns the retained items in descending order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    ///
+    /// let mut top = TopK::new(3);
+    /// top.extend([1, 9, 4, 7, 2]);
+    ///
+    /// assert_eq!(top.into_sorted_vec(), [9, 7, 4]);
+    /// ```
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap.into_sorted_vec()
+    }
+}
+
+impl<T> TopK<T> {
+    /// Returns the maximum number of items that are kept.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let top = TopK::<i32>::new(100);
+    ///
+    /// assert_eq!(top.bound(), 100);
+    /// ```
+    #[must_use]
+    pub fn bound(&self) -> usize {
+        self.k
+    }
+
+    /// Returns an iterator visiting all retained items, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let mut top = TopK::new(2);
+    /// top.extend([1, 2, 3]);
+    ///
+    /// // Print 2, 3 in arbitrary order
+    /// for x in top.iter() {
+    ///     println!("{x}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.heap.iter()
+    }
+
+    /// Consumes the `TopK` and returns the retained items in arbitrary order.
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap.into_vec()
+    }
+
+    /// Returns the number of retained items.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use proptest_binary_heap_example::TopK;
+    /// let mut top = TopK::new(2);
+    /// top.extend([1, 2, 3]);
+    ///
+    /// assert_eq!(top.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if no items are retained.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Checks if `k` items are retained, so that offering another item evicts one.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.heap.len() >= self.k
+    }
+
+    /// Drops all retained items.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.offer(item);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a TopK<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}